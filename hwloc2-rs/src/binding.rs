@@ -0,0 +1,437 @@
+//! Flags and types for
+//! [CPU binding](https://www.open-mpi.org/projects/hwloc/doc/v2.7.1/a00166.php) and
+//! [memory binding](https://www.open-mpi.org/projects/hwloc/doc/v2.7.1/a00167.php).
+//!
+//! Not all systems support all kinds of binding; [`Topology::support`] may be used to query
+//! which are actually supported.
+//!
+//! [`Topology::support`]: crate::topology::Topology::support
+
+use std::{marker::PhantomData, os::unix::thread::JoinHandleExt, thread::JoinHandle};
+
+use enum_primitive_derive::Primitive;
+
+use crate::{
+    bitmap::{Bitmap, CpuSet, NodeSet},
+    error::Error,
+    topology::Topology,
+};
+
+bitflags::bitflags! {
+    /// Process/Thread binding flags.
+    ///
+    /// These bit flags can be used to refine the binding policy.
+    ///
+    /// The default (empty) is to bind the current process, assumed to be single-threaded, in a
+    /// non-strict way. This is the most portable way to bind as all operating systems usually
+    /// provide it.
+    #[derive(Default)]
+    #[repr(C)]
+    pub struct CpuBindFlags: i32 {
+        /// Bind all threads of the current (possibly) multithreaded process.
+        const PROCESS = hwloc2_sys::hwloc_cpubind_flags_t_HWLOC_CPUBIND_PROCESS as i32;
+
+        /// Bind current thread of current process.
+        const THREAD = hwloc2_sys::hwloc_cpubind_flags_t_HWLOC_CPUBIND_THREAD as i32;
+
+        /// Request for strict binding from the OS.
+        ///
+        /// By default, when the designated CPUs are all busy while other CPUs are idle, operating
+        /// systems may execute the thread/process on those other CPUs instead of the designated
+        /// CPUs, to let them progress anyway. Strict binding means that the thread/process will
+        /// _never_ execute on other CPUs than the designated CPUs, even when those are busy with
+        /// other tasks and other CPUs are idle.
+        ///
+        /// Depending on the operating system, strict binding may not be possible (e.g., the OS
+        /// does not implement it) or not allowed (e.g., for an administrative reasons), and the
+        /// binding function will fail in that case.
+        ///
+        /// When retrieving the binding of a process, this flag checks whether all its threads
+        /// actually have the same binding. If the flag is not given, the binding of each thread
+        /// will be accumulated. This flag is meaningless when retrieving the binding of a thread.
+        const STRICT = hwloc2_sys::hwloc_cpubind_flags_t_HWLOC_CPUBIND_STRICT as i32;
+
+        /// Avoid any effect on memory binding.
+        ///
+        /// On some operating systems, some CPU binding function would also bind the memory on the
+        /// corresponding NUMA node. It is often not a problem for the application, but if it is,
+        /// setting this flag will make hwloc avoid using OS functions that would also bind memory.
+        /// This will however reduce the support of CPU bindings, i.e. potentially fail with
+        /// `ENOSYS` in some cases.
+        ///
+        /// This flag is only meaningful when used with functions that set the CPU binding. It is
+        /// ignored when used with functions that get CPU binding information.
+        const NOMEMBIND = hwloc2_sys::hwloc_cpubind_flags_t_HWLOC_CPUBIND_NOMEMBIND as i32;
+    }
+}
+
+/// Memory binding policy.
+///
+/// Only one policy can be used at a time. Not all systems support all kinds of binding;
+/// [`Support::membind`] may be used to query about the actual memory binding policy support in
+/// the currently used operating system.
+///
+/// [`Support::membind`]: crate::topology::support::Support::membind
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Primitive)]
+#[repr(i32)]
+pub enum MemBindPolicy {
+    /// Reset the memory allocation policy to the system default.
+    ///
+    /// Depending on the operating system, this may correspond to [`MemBindPolicy::FirstTouch`]
+    /// (Linux, FreeBSD), or [`MemBindPolicy::Bind`] (AIX, HP-UX, Solaris, Windows). This policy is
+    /// never returned by get membind functions. The set argument is ignored.
+    Default = hwloc2_sys::hwloc_membind_policy_t_HWLOC_MEMBIND_DEFAULT,
+
+    /// Allocate each memory page individually on the local NUMA node of the thread that touches
+    /// it.
+    ///
+    /// The given nodeset should usually be [`Topology::topology_nodeset`] so that the touching
+    /// thread may run and allocate on any node in the system.
+    ///
+    /// [`Topology::topology_nodeset`]: crate::topology::Topology::topology_nodeset
+    FirstTouch = hwloc2_sys::hwloc_membind_policy_t_HWLOC_MEMBIND_FIRSTTOUCH,
+
+    /// Allocate memory on the specified nodes.
+    Bind = hwloc2_sys::hwloc_membind_policy_t_HWLOC_MEMBIND_BIND,
+
+    /// Allocate memory on the given nodes in an interleaved / round-robin manner.
+    ///
+    /// The precise layout of the memory across multiple NUMA nodes is OS/system specific.
+    /// Interleaving can be useful when threads distributed across the specified NUMA nodes will
+    /// all be accessing the whole memory range concurrently, since the interleave will then
+    /// balance the memory references.
+    Interleave = hwloc2_sys::hwloc_membind_policy_t_HWLOC_MEMBIND_INTERLEAVE,
+
+    /// For each page bound with this policy, by next time it is touched (and next time only), it
+    /// is moved from its current location to the local NUMA node of the thread where the memory
+    /// reference occurred (if it needs to be moved at all).
+    NextTouch = hwloc2_sys::hwloc_membind_policy_t_HWLOC_MEMBIND_NEXTTOUCH,
+
+    /// Returned by get membind functions when multiple threads or parts of a memory area have
+    /// differing memory binding policies.
+    ///
+    /// Also returned when binding is unknown because binding hooks are empty when the topology is
+    /// loaded from XML without [`Flags::IS_THISSYSTEM`], etc. It cannot be used to set a binding.
+    ///
+    /// [`Flags::IS_THISSYSTEM`]: crate::topology::flags::Flags::IS_THISSYSTEM
+    Mixed = hwloc2_sys::hwloc_membind_policy_t_HWLOC_MEMBIND_MIXED,
+}
+
+bitflags::bitflags! {
+    /// Memory binding flags.
+    ///
+    /// These flags can be used to refine the binding policy. All flags can be logically OR'ed
+    /// together with the exception of [`MemBindFlags::PROCESS`] and [`MemBindFlags::THREAD`];
+    /// these two flags are mutually exclusive.
+    ///
+    /// Whether the given set is a CPU set or a nodeset is specified through [`MemBindSet`].
+    #[derive(Default)]
+    #[repr(C)]
+    pub struct MemBindFlags: i32 {
+        /// Set policy for all threads of the specified (possibly multithreaded) process.
+        ///
+        /// This flag is mutually exclusive with [`MemBindFlags::THREAD`].
+        const PROCESS = hwloc2_sys::hwloc_membind_flags_t_HWLOC_MEMBIND_PROCESS as i32;
+
+        /// Set policy for a specific thread of the current process.
+        ///
+        /// This flag is mutually exclusive with [`MemBindFlags::PROCESS`].
+        const THREAD = hwloc2_sys::hwloc_membind_flags_t_HWLOC_MEMBIND_THREAD as i32;
+
+        /// Request strict binding from the OS.
+        ///
+        /// The function will fail if the binding can not be guaranteed / completely enforced.
+        /// This flag has slightly different meanings depending on which function it is used with.
+        const STRICT = hwloc2_sys::hwloc_membind_flags_t_HWLOC_MEMBIND_STRICT as i32;
+
+        /// Migrate existing allocated memory.
+        ///
+        /// If the memory cannot be migrated and the [`MemBindFlags::STRICT`] flag is passed, an
+        /// error will be returned.
+        const MIGRATE = hwloc2_sys::hwloc_membind_flags_t_HWLOC_MEMBIND_MIGRATE as i32;
+
+        /// Avoid any effect on CPU binding.
+        ///
+        /// On some operating systems, some underlying memory binding functions also bind the
+        /// application to the corresponding CPU(s). Using this flag will cause hwloc to avoid
+        /// using OS functions that could potentially affect CPU bindings. Note, however, that
+        /// using this flag may reduce hwloc's overall memory binding support; some of the memory
+        /// binding functions may fail with `ENOSYS` when used with it.
+        const NOCPUBIND = hwloc2_sys::hwloc_membind_flags_t_HWLOC_MEMBIND_NOCPUBIND as i32;
+    }
+}
+
+impl MemBindFlags {
+    /// The raw hwloc flags, with `HWLOC_MEMBIND_BYNODESET` also set, so that the given or
+    /// retrieved bitmap is considered a nodeset.
+    pub(crate) fn bits_by_nodeset(self) -> i32 {
+        self.bits() | hwloc2_sys::hwloc_membind_flags_t_HWLOC_MEMBIND_BYNODESET as i32
+    }
+}
+
+/// The set of NUMA nodes that memory should be bound to, specified either directly as a nodeset,
+/// or as a CPU set whose local NUMA nodes should be used.
+///
+/// Memory binding by CPU set cannot work for CPU-less NUMA memory nodes; binding by nodeset
+/// should therefore be preferred whenever possible.
+#[derive(Debug, Clone, Copy)]
+pub enum MemBindSet<'a> {
+    /// Bind to the NUMA nodes that are local to the PUs of the CPU set.
+    CpuSet(&'a CpuSet),
+    /// Bind to the NUMA nodes of the nodeset.
+    NodeSet(&'a NodeSet),
+}
+
+impl MemBindSet<'_> {
+    /// The underlying bitmap.
+    pub(crate) fn bitmap(&self) -> &Bitmap {
+        match self {
+            MemBindSet::CpuSet(set) | MemBindSet::NodeSet(set) => set,
+        }
+    }
+
+    /// The raw hwloc flags for `flags` applied on this set.
+    pub(crate) fn raw_flags(&self, flags: MemBindFlags) -> i32 {
+        match self {
+            MemBindSet::CpuSet(_) => flags.bits(),
+            MemBindSet::NodeSet(_) => flags.bits_by_nodeset(),
+        }
+    }
+}
+
+/// A handle to a thread whose binding may be set or retrieved, i.e. a `pthread_t`.
+///
+/// A `ThreadHandle` may be obtained for the current thread via [`ThreadHandle::current`], for a
+/// thread spawned through [`std::thread`] via its [`JoinHandle`] (borrowing it, so that the thread
+/// cannot be joined while the handle is in use), or from a raw `pthread_t` via the `unsafe`
+/// [`ThreadHandle::from_raw`].
+///
+/// Since the validity of a `pthread_t` depends on the thread that it refers to, a `ThreadHandle`
+/// can be neither sent nor shared across threads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThreadHandle<'a> {
+    thread: libc::pthread_t,
+    _marker: PhantomData<&'a *const ()>,
+}
+
+impl ThreadHandle<'_> {
+    /// A handle to the calling thread.
+    pub fn current() -> ThreadHandle<'static> {
+        ThreadHandle {
+            // SAFETY: `pthread_self()` always succeeds.
+            thread: unsafe { libc::pthread_self() },
+            _marker: PhantomData,
+        }
+    }
+
+    /// Wrap the given raw `pthread_t` into a `ThreadHandle`.
+    ///
+    /// # Safety
+    ///
+    /// The caller must make sure that `thread` refers to a thread of the current process that
+    /// has not been joined or detached-and-exited, and that it remains so for as long as the
+    /// returned handle (or any copy of it) is in use.
+    pub unsafe fn from_raw<'a>(thread: libc::pthread_t) -> ThreadHandle<'a> {
+        ThreadHandle {
+            thread,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The underlying `pthread_t`.
+    pub fn as_raw(&self) -> libc::pthread_t {
+        self.thread
+    }
+}
+
+impl<'a, T> From<&'a JoinHandle<T>> for ThreadHandle<'a> {
+    fn from(handle: &'a JoinHandle<T>) -> Self {
+        Self {
+            thread: handle.as_pthread_t(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// A guard that restores the previous CPU binding of the current process or thread when dropped.
+///
+/// It is created by [`Topology::bind_scoped`], and, since the binding that it restores may be
+/// specific to the current thread, it can be neither sent nor shared across threads.
+///
+/// [`Topology::bind_scoped`]: crate::topology::Topology::bind_scoped
+#[must_use = "the previous CPU binding is restored as soon as the guard is dropped"]
+#[derive(Debug)]
+pub struct CpuBindGuard<'topo> {
+    topo: &'topo Topology,
+    previous: CpuSet,
+    flags: CpuBindFlags,
+    restored: bool,
+    _marker: PhantomData<*const ()>,
+}
+
+impl<'topo> CpuBindGuard<'topo> {
+    /// Retrieve the current CPU binding, and then bind on `cpuset`.
+    ///
+    /// Unless only the current thread is bound, the binding of the process is retrieved strictly,
+    /// i.e., it fails if its threads have different bindings: their union would otherwise be
+    /// restored on all of them, widening the binding of some.
+    pub(crate) fn bind(
+        topo: &'topo Topology,
+        cpuset: &CpuSet,
+        flags: CpuBindFlags,
+    ) -> Result<Self, Error> {
+        let previous = if flags.contains(CpuBindFlags::THREAD) {
+            topo.cpubind(CpuBindFlags::THREAD)?
+        } else {
+            topo.cpubind((flags & CpuBindFlags::PROCESS) | CpuBindFlags::STRICT)?
+        };
+        topo.set_cpubind(cpuset, flags)?;
+        Ok(Self {
+            topo,
+            previous,
+            flags,
+            restored: false,
+            _marker: PhantomData,
+        })
+    }
+
+    /// The CPU binding that is restored when the guard is dropped.
+    pub fn previous(&self) -> &CpuSet {
+        &self.previous
+    }
+
+    /// Restore the previous CPU binding right away, reporting any failure to do so; dropping the
+    /// guard instead ignores it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::CpuBindSet`] if hwloc fails to restore the previous binding.
+    ///
+    /// [`Error::CpuBindSet`]: crate::error::Error::CpuBindSet
+    pub fn restore(mut self) -> Result<(), Error> {
+        self.restored = true;
+        self.topo.set_cpubind(&self.previous, self.flags)
+    }
+}
+
+impl Drop for CpuBindGuard<'_> {
+    fn drop(&mut self) {
+        if !self.restored {
+            let _ = self.topo.set_cpubind(&self.previous, self.flags);
+        }
+    }
+}
+
+/// A guard that restores the previous memory binding of the current process or thread when
+/// dropped.
+///
+/// It is created by [`Topology::membind_scoped`], and, since the binding that it restores may be
+/// specific to the current thread, it can be neither sent nor shared across threads.
+///
+/// [`Topology::membind_scoped`]: crate::topology::Topology::membind_scoped
+#[must_use = "the previous memory binding is restored as soon as the guard is dropped"]
+#[derive(Debug)]
+pub struct MemBindGuard<'topo> {
+    topo: &'topo Topology,
+    previous: NodeSet,
+    policy: MemBindPolicy,
+    flags: MemBindFlags,
+    restored: bool,
+    _marker: PhantomData<*const ()>,
+}
+
+impl<'topo> MemBindGuard<'topo> {
+    /// Retrieve the current memory binding, make sure that it can be restored by applying it
+    /// again, and then bind to `set`.
+    pub(crate) fn bind(
+        topo: &'topo Topology,
+        set: MemBindSet<'_>,
+        policy: MemBindPolicy,
+        flags: MemBindFlags,
+    ) -> Result<Self, Error> {
+        let (previous, previous_policy) =
+            topo.membind(flags & (MemBindFlags::PROCESS | MemBindFlags::THREAD))?;
+        let mut guard = Self {
+            topo,
+            previous,
+            policy: previous_policy,
+            flags: flags & !MemBindFlags::MIGRATE,
+            restored: true,
+            _marker: PhantomData,
+        };
+        // A binding that cannot be restored (e.g., `MemBindPolicy::Mixed`) would otherwise leak
+        // out of the guard, since errors are ignored when it is dropped.
+        if previous_policy == MemBindPolicy::Mixed {
+            return Err(Error::MemBindSet);
+        }
+        guard.set_previous()?;
+        topo.set_membind(set, policy, flags)?;
+        guard.restored = false;
+        Ok(guard)
+    }
+
+    /// The memory binding that is restored when the guard is dropped.
+    pub fn previous(&self) -> (&NodeSet, MemBindPolicy) {
+        (&self.previous, self.policy)
+    }
+
+    /// Restore the previous memory binding right away, reporting any failure to do so; dropping
+    /// the guard instead ignores it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MemBindSet`] if hwloc fails to restore the previous binding (e.g., if the
+    /// previous policy was [`MemBindPolicy::Mixed`], which cannot be set).
+    ///
+    /// [`Error::MemBindSet`]: crate::error::Error::MemBindSet
+    pub fn restore(mut self) -> Result<(), Error> {
+        self.restored = true;
+        self.set_previous()
+    }
+
+    /// Bind back to the previous nodeset, with the previous policy.
+    fn set_previous(&self) -> Result<(), Error> {
+        self.topo
+            .set_membind(MemBindSet::NodeSet(&self.previous), self.policy, self.flags)
+    }
+}
+
+impl Drop for MemBindGuard<'_> {
+    fn drop(&mut self) {
+        if !self.restored {
+            let _ = self.set_previous();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_traits::FromPrimitive;
+
+    use super::{CpuBindFlags, MemBindFlags, MemBindPolicy, ThreadHandle};
+
+    #[test]
+    fn cpubind_flags() {
+        assert!(CpuBindFlags::default().is_empty());
+        assert_eq!(0b1111, CpuBindFlags::all().bits());
+    }
+
+    #[test]
+    fn membind_flags_and_policies() {
+        assert!(MemBindFlags::default().is_empty());
+        assert_eq!(0b11111, MemBindFlags::all().bits());
+        assert_eq!(Some(MemBindPolicy::Mixed), MemBindPolicy::from_i32(-1));
+        assert_eq!(Some(MemBindPolicy::Interleave), MemBindPolicy::from_i32(3));
+    }
+
+    #[test]
+    fn thread_handles() {
+        let current = ThreadHandle::current();
+        assert_eq!(unsafe { libc::pthread_self() }, current.as_raw());
+
+        let handle = std::thread::spawn(|| ThreadHandle::current().as_raw());
+        let thread = ThreadHandle::from(&handle).as_raw();
+        assert_ne!(current.as_raw(), thread);
+        assert_eq!(thread, handle.join().unwrap());
+    }
+}
@@ -21,6 +21,11 @@ pub struct Bitmap {
     manage: bool,
 }
 
+// SAFETY: hwloc bitmaps are plain heap-allocated data without any thread affinity, and hwloc
+// never modifies a bitmap through a `*const` pointer, which is what `&self` methods pass it.
+unsafe impl Send for Bitmap {}
+unsafe impl Sync for Bitmap {}
+
 /// A CPU set is a bitmap whose bits are set according to CPU physical OS indexes.
 ///
 /// It may be consulted and modified with the [`Bitmap`] API.
@@ -285,6 +290,33 @@ impl Bitmap {
         })
     }
 
+    /// Allocates a new `Bitmap` holding a copy of the hwloc bitmap that `bitmap` points to.
+    ///
+    /// This is how bitmaps owned by a topology (e.g., objects' CPU sets) are handed out, so that
+    /// they can never be modified through a `Bitmap`.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::BitmapNullPointer`] if the provided `bitmap` pointer is `NULL`.
+    /// - [`Error::BitmapAlloc`] if hwloc failed to allocate the new bitmap.
+    ///
+    /// # Safety
+    ///
+    /// The caller must make sure that the provided pointer, if not `NULL`, points to a valid hwloc
+    /// bitmap.
+    ///
+    /// [`Error::BitmapNullPointer`]: crate::error::Error::BitmapNullPointer
+    /// [`Error::BitmapAlloc`]: crate::error::Error::BitmapAlloc
+    pub(crate) unsafe fn copy_from_raw(
+        bitmap: *const hwloc2_sys::hwloc_bitmap_s,
+    ) -> Result<Self, Error> {
+        if bitmap.is_null() {
+            return Err(Error::BitmapNullPointer);
+        }
+        let ptr = NonNull::new(hwloc2_sys::hwloc_bitmap_dup(bitmap)).ok_or(Error::BitmapAlloc)?;
+        Ok(Self { ptr, manage: true })
+    }
+
     /// Returns the contained hwloc bitmap pointer, for interaction with hwloc.
     pub fn as_ptr(&self) -> *mut hwloc2_sys::hwloc_bitmap_s {
         self.ptr.as_ptr()
@@ -0,0 +1,483 @@
+//! Matrices of
+//! [distances between objects](https://www.open-mpi.org/projects/hwloc/doc/v2.7.1/a00184.php),
+//! e.g., the latencies between NUMA nodes (ACPI SLIT).
+//!
+//! Distances may be retrieved through [`Topology::distances`] and its variants.
+//!
+//! [`Topology::distances`]: crate::topology::Topology::distances
+
+use std::{
+    ffi::{CStr, CString},
+    fmt,
+    mem::ManuallyDrop,
+    ptr::NonNull,
+    slice,
+};
+
+use crate::{
+    error::Error,
+    object::{Object, ObjectId},
+    topology::Topology,
+};
+
+bitflags::bitflags! {
+    /// Kinds of distance matrices.
+    ///
+    /// The kind of a [`Distances`] is an OR'ed set of these flags. When used as a filter for
+    /// retrieving distances (e.g., in [`Topology::distances`]), an empty set matches all
+    /// matrices; otherwise, if it contains some `FROM_*` flags, only matrices whose kind matches
+    /// one of them are returned, and, if it contains some `MEANS_*` flags, only matrices whose
+    /// kind matches one of them are returned.
+    ///
+    /// [`Topology::distances`]: crate::topology::Topology::distances
+    #[derive(Default)]
+    #[repr(C)]
+    pub struct DistancesKind: u64 {
+        /// These distances were obtained from the operating system or hardware.
+        const FROM_OS = hwloc2_sys::hwloc_distances_kind_e_HWLOC_DISTANCES_KIND_FROM_OS as u64;
+        /// These distances were provided by the user.
+        const FROM_USER = hwloc2_sys::hwloc_distances_kind_e_HWLOC_DISTANCES_KIND_FROM_USER as u64;
+        /// Distance values are similar to latencies between objects.
+        ///
+        /// Values are smaller for closer objects, hence minimal on the diagonal of the matrix
+        /// (latency to self). Values may also be larger than the latencies, e.g., ACPI SLIT
+        /// distances from Linux are relative to the local latency (10).
+        const MEANS_LATENCY =
+            hwloc2_sys::hwloc_distances_kind_e_HWLOC_DISTANCES_KIND_MEANS_LATENCY as u64;
+        /// Distance values are similar to bandwidths between objects.
+        ///
+        /// Values are higher for closer objects, hence maximal on the diagonal of the matrix
+        /// (bandwidth to self). Values may also be different from the actual bandwidths.
+        const MEANS_BANDWIDTH =
+            hwloc2_sys::hwloc_distances_kind_e_HWLOC_DISTANCES_KIND_MEANS_BANDWIDTH as u64;
+        /// The matrix may contain objects of different types, which cannot be filtered by type.
+        const HETEROGENEOUS_TYPES =
+            hwloc2_sys::hwloc_distances_kind_e_HWLOC_DISTANCES_KIND_HETEROGENEOUS_TYPES as u64;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags for adding a new distance matrix to a topology.
+    ///
+    /// Flags should be given to [`DistancesBuilder::commit`].
+    #[derive(Default)]
+    #[repr(C)]
+    pub struct DistancesAddFlags: u64 {
+        /// Try to group objects based on the newly provided distance information.
+        ///
+        /// This is ignored for distances between objects of different types.
+        const GROUP = hwloc2_sys::hwloc_distances_add_flag_e_HWLOC_DISTANCES_ADD_FLAG_GROUP as u64;
+        /// If grouping, consider the distance values as inaccurate and relax the comparisons
+        /// during the grouping algorithms. The actual accuracy may be modified through the
+        /// `HWLOC_GROUPING_ACCURACY` environment variable (see
+        /// [Environment Variables](https://www.open-mpi.org/projects/hwloc/doc/v2.7.1/a00358.php)).
+        const GROUP_INACCURATE =
+            hwloc2_sys::hwloc_distances_add_flag_e_HWLOC_DISTANCES_ADD_FLAG_GROUP_INACCURATE
+                as u64;
+    }
+}
+
+/// Transformations of distance matrices, which may be applied via [`Distances::transform`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u32)]
+pub enum DistancesTransform {
+    /// Remove the objects that have been removed from the matrix, e.g., via
+    /// [`Distances::retain_objects`], which already applies this transformation.
+    ///
+    /// At least 2 objects must remain, and the kind of the matrix is updated with or without
+    /// [`DistancesKind::HETEROGENEOUS_TYPES`] according to the remaining objects.
+    RemoveNull = hwloc2_sys::hwloc_distances_transform_e_HWLOC_DISTANCES_TRANSFORM_REMOVE_NULL,
+
+    /// Replace bandwidth values with a number of links.
+    ///
+    /// Usually all values will be either 0 (no link) or 1 (one link). However some matrices could
+    /// get larger values if some pairs of peers are connected by different numbers of links.
+    /// Values on the diagonal are set to 0.
+    ///
+    /// This transformation only applies to bandwidth matrices.
+    Links = hwloc2_sys::hwloc_distances_transform_e_HWLOC_DISTANCES_TRANSFORM_LINKS,
+
+    /// Merge switches with multiple ports into a single object.
+    ///
+    /// This currently only applies to NVSwitches where GPUs seem connected to different separate
+    /// switch ports in the `"NVLinkBandwidth"` matrix. This transformation will replace all of
+    /// them with the same port connected to all GPUs. Other ports are removed.
+    MergeSwitchPorts =
+        hwloc2_sys::hwloc_distances_transform_e_HWLOC_DISTANCES_TRANSFORM_MERGE_SWITCH_PORTS,
+
+    /// Apply a transitive closure to the matrix to connect objects across switches.
+    ///
+    /// This currently only applies to GPUs and NVSwitches in the `"NVLinkBandwidth"` matrix. All
+    /// pairs of GPUs will be reported as directly connected.
+    TransitiveClosure =
+        hwloc2_sys::hwloc_distances_transform_e_HWLOC_DISTANCES_TRANSFORM_TRANSITIVE_CLOSURE,
+}
+
+/// A matrix of distances between a set of objects of a [`Topology`].
+///
+/// The value from the i-th to the j-th object is stored at index `i * nbobjs() + j` of
+/// [`Distances::values`], where objects are in the same order as in [`Distances::objects`];
+/// [`Distances::value`] may be used instead to look values up by object.
+///
+/// The matrix is released when dropped.
+///
+/// [`Topology`]: crate::topology::Topology
+pub struct Distances<'topo> {
+    topo: &'topo Topology,
+    ptr: NonNull<hwloc2_sys::hwloc_distances_s>,
+}
+
+// SAFETY: A `Distances` exclusively owns the matrix that was handed out by hwloc, and it only
+// shares the `Topology`, which is `Sync`.
+unsafe impl Send for Distances<'_> {}
+unsafe impl Sync for Distances<'_> {}
+
+impl<'topo> Distances<'topo> {
+    /// Collect the distance matrices retrieved by `get`, which follows the calling convention of
+    /// `hwloc_distances_get()` (i.e., it is given the capacity and the array of the matrices).
+    pub(crate) fn collect<F>(topo: &'topo Topology, mut get: F) -> Result<Vec<Self>, Error>
+    where
+        F: FnMut(*mut libc::c_uint, *mut *mut hwloc2_sys::hwloc_distances_s) -> libc::c_int,
+    {
+        // Query the number of matrices first, and then retrieve them.
+        let mut nr = 0;
+        if -1 == get(&mut nr, std::ptr::null_mut()) {
+            return Err(Error::DistancesGet);
+        }
+        let mut ptrs = vec![std::ptr::null_mut(); nr as usize];
+        let capacity = nr;
+        if -1 == get(&mut nr, ptrs.as_mut_ptr()) {
+            return Err(Error::DistancesGet);
+        }
+        Ok(ptrs
+            .into_iter()
+            .take(nr.min(capacity) as usize)
+            .filter_map(NonNull::new)
+            .map(|ptr| Self { topo, ptr })
+            .collect())
+    }
+
+    /// A description of what the matrix contains (e.g., `"NUMALatency"` for hardware-provided
+    /// NUMA distances, i.e., ACPI SLIT), if known.
+    pub fn name(&self) -> Option<String> {
+        // SAFETY: `self.topo` is a valid, loaded topology, and `self.ptr` is a valid distances
+        // structure that was retrieved from it.
+        let name =
+            unsafe { hwloc2_sys::hwloc_distances_get_name(self.topo.as_ptr(), self.as_ptr()) };
+        if name.is_null() {
+            return None;
+        }
+        // SAFETY: Since `name` != NULL, it should be a valid C string according to hwloc, which
+        // remains owned by the topology (i.e., it must not be freed here).
+        let cstr = unsafe { CStr::from_ptr(name) };
+        cstr.to_str().ok().map(|s| s.to_owned())
+    }
+
+    /// The kind of distances in the matrix.
+    pub fn kind(&self) -> DistancesKind {
+        DistancesKind::from_bits_truncate(self.raw().kind)
+    }
+
+    /// Number of objects described by the matrix.
+    pub fn nbobjs(&self) -> usize {
+        self.raw().nbobjs as usize
+    }
+
+    /// The objects described by the matrix, in the order of its rows and columns.
+    pub fn objects(&self) -> impl Iterator<Item = Object<'topo>> + '_ {
+        self.raw_objects()
+            .iter()
+            // SAFETY: The objects of the matrix are valid objects of `self.topo`.
+            .map(|&obj| unsafe { Object::new(obj) })
+    }
+
+    /// The values of the matrix, stored as a one-dimension array in row-major order; i.e., the
+    /// value from the i-th to the j-th object is stored at index `i * nbobjs() + j`.
+    pub fn values(&self) -> &[u64] {
+        let raw = self.raw();
+        let n = raw.nbobjs as usize;
+        // SAFETY: `values` holds `nbobjs * nbobjs` values, which live as long as `self`.
+        unsafe { slice::from_raw_parts(raw.values, n * n) }
+    }
+
+    /// The index of `obj` among the objects of the matrix, if it is described by it.
+    pub fn object_index(&self, obj: Object<'_>) -> Option<usize> {
+        self.raw_objects()
+            .iter()
+            .position(|&o| std::ptr::eq(o, obj.as_ptr()))
+    }
+
+    /// The value from object `from` to object `to`, if both are described by the matrix.
+    pub fn value(&self, from: Object<'_>, to: Object<'_>) -> Option<u64> {
+        let (i, j) = (self.object_index(from)?, self.object_index(to)?);
+        Some(self.values()[i * self.nbobjs() + j])
+    }
+
+    /// Apply the given transformation to the matrix.
+    ///
+    /// Only this copy of the matrix is transformed; the one stored in the topology (which may be
+    /// retrieved again or exported to XML) is not modified.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::DistancesTransform`] in case of failure reported by hwloc (e.g., if the
+    /// transformation does not apply to this matrix), in which case the matrix is left
+    /// unmodified.
+    ///
+    /// [`Error::DistancesTransform`]: crate::error::Error::DistancesTransform
+    pub fn transform(&mut self, transform: DistancesTransform) -> Result<(), Error> {
+        // SAFETY: `self.topo` is a valid, loaded topology, and `self.ptr` is a valid distances
+        // structure that was retrieved from it and is exclusively borrowed; none of the
+        // transformations takes any attributes.
+        match unsafe {
+            hwloc2_sys::hwloc_distances_transform(
+                self.topo.as_ptr(),
+                self.as_ptr(),
+                transform as _,
+                std::ptr::null_mut(),
+                0,
+            )
+        } {
+            -1 => Err(Error::DistancesTransform(transform)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Retain only the objects (along with their rows and columns of values) for which `f`
+    /// returns `true`.
+    ///
+    /// Just like [`Distances::transform`], only this copy of the matrix is modified.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::DistancesTransform`] if fewer than 2 objects would remain, in which case
+    /// the matrix is left unmodified.
+    ///
+    /// [`Error::DistancesTransform`]: crate::error::Error::DistancesTransform
+    pub fn retain_objects<F>(&mut self, mut f: F) -> Result<(), Error>
+    where
+        F: FnMut(Object<'topo>) -> bool,
+    {
+        let (objs, nbobjs) = (self.raw().objs, self.raw().nbobjs as usize);
+        // SAFETY: `objs` holds `nbobjs` object pointers, which live as long as `self`, and which
+        // are exclusively borrowed.
+        let original = unsafe { slice::from_raw_parts(objs, nbobjs) }.to_vec();
+        // All decisions are made before any object is replaced by NULL, so that no NULL object
+        // remains in the matrix if `f` panics.
+        let retain: Vec<bool> = original
+            .iter()
+            // SAFETY: The objects of the matrix are valid objects of `self.topo`.
+            .map(|&obj| f(unsafe { Object::new(obj) }))
+            .collect();
+        for (idx, _) in retain.iter().enumerate().filter(|(_, &keep)| !keep) {
+            // SAFETY: As above.
+            unsafe { *objs.add(idx) = std::ptr::null_mut() };
+        }
+        // NULL objects must not outlive this function, since `objects()` expects none: they are
+        // either removed, or restored on failure, in which case hwloc leaves the matrix untouched.
+        self.transform(DistancesTransform::RemoveNull)
+            .inspect_err(|_| {
+                // SAFETY: As above.
+                unsafe { slice::from_raw_parts_mut(objs, nbobjs) }.copy_from_slice(&original);
+            })
+    }
+
+    /// Give up the ownership of the underlying hwloc distances structure, which must then be
+    /// released (or removed) through hwloc.
+    pub(crate) fn into_raw(self) -> *mut hwloc2_sys::hwloc_distances_s {
+        ManuallyDrop::new(self).as_ptr()
+    }
+
+    /// The underlying hwloc distances structure.
+    pub(crate) fn as_ptr(&self) -> *mut hwloc2_sys::hwloc_distances_s {
+        self.ptr.as_ptr()
+    }
+
+    fn raw(&self) -> &hwloc2_sys::hwloc_distances_s {
+        // SAFETY: `self.ptr` is a valid distances structure, which lives as long as `self`.
+        unsafe { self.ptr.as_ref() }
+    }
+
+    fn raw_objects(&self) -> &[hwloc2_sys::hwloc_obj_t] {
+        let raw = self.raw();
+        // SAFETY: `objs` holds `nbobjs` objects, which live as long as `self`.
+        unsafe { slice::from_raw_parts(raw.objs, raw.nbobjs as usize) }
+    }
+}
+
+impl fmt::Debug for Distances<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Distances")
+            .field("name", &self.name())
+            .field("kind", &self.kind())
+            .field("objects", &self.objects().collect::<Vec<_>>())
+            .field("values", &self.values())
+            .finish()
+    }
+}
+
+impl Drop for Distances<'_> {
+    fn drop(&mut self) {
+        // SAFETY: `self.ptr` was retrieved from `self.topo`, and it is released only once, here.
+        unsafe { hwloc2_sys::hwloc_distances_release(self.topo.as_ptr(), self.as_ptr()) }
+    }
+}
+
+/// Builder for adding a new, user-provided distance matrix to a [`Topology`].
+///
+/// It is created by [`Topology::add_distances`]. Its objects and values must be set before
+/// calling [`DistancesBuilder::commit`], which actually adds the matrix to the topology; the
+/// matrix may then be retrieved (or exported to XML) just like the ones provided by the OS.
+///
+/// ```no_run
+/// # use hwloc2::{distances::{DistancesAddFlags, DistancesKind}, object::ObjectId, ObjectType, Topology};
+/// let mut topo = Topology::builder()?.build()?;
+/// let packages: Vec<ObjectId> = (0..topo.nbobjs_by_type(ObjectType::Package) as u32)
+///     .filter_map(|idx| topo.object_by_type(ObjectType::Package, idx))
+///     .map(ObjectId::from)
+///     .collect();
+/// let n = packages.len();
+/// let bandwidths: Vec<u64> = (0..n * n).map(|i| if i % (n + 1) == 0 { 100 } else { 40 }).collect();
+/// topo.add_distances(
+///     "SocketBandwidth",
+///     DistancesKind::FROM_USER | DistancesKind::MEANS_BANDWIDTH,
+/// )
+/// .objects(&packages)
+/// .values(&bandwidths)
+/// .commit(DistancesAddFlags::empty())?;
+/// # Ok::<(), hwloc2::Error>(())
+/// ```
+///
+/// [`Topology`]: crate::topology::Topology
+/// [`Topology::add_distances`]: crate::topology::Topology::add_distances
+#[derive(Debug)]
+pub struct DistancesBuilder<'topo> {
+    topo: &'topo mut Topology,
+    name: String,
+    kind: DistancesKind,
+    objects: Vec<ObjectId>,
+    values: Vec<u64>,
+}
+
+impl<'topo> DistancesBuilder<'topo> {
+    pub(crate) fn new(topo: &'topo mut Topology, name: &str, kind: DistancesKind) -> Self {
+        Self {
+            topo,
+            name: name.to_owned(),
+            kind,
+            objects: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+
+    /// Set the objects that the matrix describes, in the order of its rows and columns.
+    pub fn objects(mut self, objects: &[ObjectId]) -> Self {
+        self.objects = objects.to_vec();
+        self
+    }
+
+    /// Set the values of the matrix, as a one-dimension array in row-major order; i.e., the
+    /// value from the i-th to the j-th object at index `i * n + j`, for `n` objects.
+    pub fn values(mut self, values: &[u64]) -> Self {
+        self.values = values.to_vec();
+        self
+    }
+
+    /// Add the matrix to the topology.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::DistancesAdd`] if the kind does not contain exactly one `FROM_*` and one
+    /// `MEANS_*` flag, if there are fewer than 2 objects, if the number of values is not the
+    /// square of the number of objects, if some object does not exist in the topology, if the
+    /// name contains a NUL byte, or in case of failure reported by hwloc.
+    ///
+    /// [`Error::DistancesAdd`]: crate::error::Error::DistancesAdd
+    pub fn commit(self, flags: DistancesAddFlags) -> Result<(), Error> {
+        let error = || Error::DistancesAdd(self.name.clone());
+        let from = self.kind & (DistancesKind::FROM_OS | DistancesKind::FROM_USER);
+        let means = self.kind & (DistancesKind::MEANS_LATENCY | DistancesKind::MEANS_BANDWIDTH);
+        if from.bits().count_ones() != 1 || means.bits().count_ones() != 1 {
+            return Err(error());
+        }
+        let nbobjs = self.objects.len();
+        if nbobjs < 2 || self.values.len() != nbobjs * nbobjs {
+            return Err(error());
+        }
+        let mut objs = self
+            .objects
+            .iter()
+            .map(|&id| {
+                self.topo
+                    .object_by_id(id)
+                    .map(|obj| obj.as_ptr() as hwloc2_sys::hwloc_obj_t)
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(error)?;
+        let mut values = self.values.clone();
+        let name = CString::new(self.name.as_str()).map_err(|_| error())?;
+
+        let topo = self.topo.as_ptr();
+        // SAFETY: `topo` is a valid, loaded topology, which is exclusively borrowed, and `name`
+        // is a valid C string, which hwloc duplicates.
+        let handle = unsafe {
+            hwloc2_sys::hwloc_distances_add_create(topo, name.as_ptr(), self.kind.bits() as _, 0)
+        };
+        if handle.is_null() {
+            return Err(error());
+        }
+        // SAFETY: `handle` was just created for `topo`, `objs` are valid objects of `topo`, and
+        // `values` holds `nbobjs * nbobjs` values; hwloc copies both arrays. On failure, hwloc
+        // destroys the handle.
+        if -1
+            == unsafe {
+                hwloc2_sys::hwloc_distances_add_values(
+                    topo,
+                    handle,
+                    nbobjs as _,
+                    objs.as_mut_ptr(),
+                    values.as_mut_ptr(),
+                    0,
+                )
+            }
+        {
+            return Err(error());
+        }
+        // SAFETY: `handle` was filled with values right above; hwloc destroys it either way.
+        match unsafe { hwloc2_sys::hwloc_distances_add_commit(topo, handle, flags.bits() as _) } {
+            -1 => Err(error()),
+            _ => {
+                self.topo.refresh();
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DistancesAddFlags, DistancesKind};
+
+    #[test]
+    fn distances_add_flags() {
+        assert!(DistancesAddFlags::default().is_empty());
+        assert_eq!(
+            0b11,
+            (DistancesAddFlags::GROUP | DistancesAddFlags::GROUP_INACCURATE).bits()
+        );
+    }
+
+    #[test]
+    fn distances_kind() {
+        assert!(DistancesKind::default().is_empty());
+        assert_eq!(
+            0b11111,
+            (DistancesKind::FROM_OS
+                | DistancesKind::FROM_USER
+                | DistancesKind::MEANS_LATENCY
+                | DistancesKind::MEANS_BANDWIDTH
+                | DistancesKind::HETEROGENEOUS_TYPES)
+                .bits()
+        );
+    }
+}
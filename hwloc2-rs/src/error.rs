@@ -1,5 +1,11 @@
+use std::path::PathBuf;
+
 use crate::{
-    topology::{filters::Filter, flags::Flags},
+    distances::DistancesTransform,
+    topology::{
+        filters::Filter,
+        flags::{Flags, RestrictFlags},
+    },
     ObjectType,
 };
 
@@ -14,12 +20,68 @@ pub enum Error {
     #[error("Failed to load the actual topology object")]
     TopologyLoad,
 
+    /// Failure to load the new topology from an XML file or buffer, e.g., because the XML is
+    /// malformed, reported by hwloc.
+    ///
+    /// Depending on the XML backend of hwloc (libxml2 or its minimal built-in parser), malformed
+    /// XML is either already rejected with [`Error::TopologySetXml`] or
+    /// [`Error::TopologySetXmlBuffer`], or only once the topology is built, with this error.
+    #[error("Failed to load the topology from XML")]
+    TopologyLoadXml,
+
     /// Failure to create a valid [`Support`] object, reported by hwloc.
     ///
     /// [`Support`]: crate::topology::support::Support
     #[error("Failed to create a valid topology::Support object")]
     TopologySupport,
 
+    /// Failure to read the XML file to load the topology from, or (depending on the XML backend
+    /// of hwloc) to parse it, reported by hwloc; see also [`Error::TopologyLoadXml`].
+    #[error("Failed to use XML file '{0}' as the topology source")]
+    TopologySetXml(PathBuf),
+
+    /// Failure to use the XML buffer to load the topology from, e.g., because it contains a NUL
+    /// byte, or (depending on the XML backend of hwloc) because it cannot be parsed, reported by
+    /// hwloc; see also [`Error::TopologyLoadXml`].
+    #[error("Failed to use the provided XML buffer as the topology source")]
+    TopologySetXmlBuffer,
+
+    /// Failure to parse the synthetic description to load the topology from, reported by hwloc.
+    #[error("Failed to use synthetic description '{0}' as the topology source")]
+    TopologySetSynthetic(String),
+
+    /// Failure to duplicate the topology, reported by hwloc.
+    #[error("Failed to duplicate the topology")]
+    TopologyDup,
+
+    /// Failure to set the process whose topology view should be discovered, reported by hwloc.
+    #[error("Failed to set the topology's target process to PID {0}")]
+    TopologySetPid(libc::pid_t),
+
+    /// Failure to blacklist a discovery component, reported by hwloc.
+    #[error("Failed to blacklist discovery component '{0}'")]
+    TopologySetComponents(String),
+
+    /// Failure to restrict the topology, reported by hwloc.
+    #[error("Failed to restrict the topology (flags: {0:?})")]
+    TopologyRestrict(RestrictFlags),
+
+    /// Failure to change the allowed sets of the topology, reported by hwloc.
+    #[error("Failed to change the sets of allowed PUs and NUMA nodes")]
+    TopologyAllow,
+
+    /// Failure to export the topology to the XML file, reported by hwloc.
+    #[error("Failed to export the topology to XML file '{0}'")]
+    TopologyExportXml(PathBuf),
+
+    /// Failure to export the topology to an XML buffer, reported by hwloc.
+    #[error("Failed to export the topology to an XML buffer")]
+    TopologyExportXmlBuffer,
+
+    /// Failure to export the topology as a synthetic description, reported by hwloc.
+    #[error("Failed to export the topology as a synthetic description")]
+    TopologyExportSynthetic,
+
     /// Failure to set the flags, reported by hwloc.
     #[error("Failed to set flags {0:?}")]
     TopologyFlags(Flags),
@@ -114,4 +176,122 @@ pub enum Error {
     /// Failure to bind the current process or thread on a given CPU, reported by hwloc.
     #[error("Failed to bind the current process or thread on given CPU")]
     CpuBindSet,
+
+    /// Failure to retrieve the CPU binding of the current process or thread, reported by hwloc.
+    #[error("Failed to retrieve the CPU binding of the current process or thread")]
+    CpuBindGet,
+
+    /// Failure to bind a process on a given CPU, reported by hwloc.
+    #[error("Failed to bind process {0} on given CPU")]
+    ProcCpuBindSet(libc::pid_t),
+
+    /// Failure to retrieve the CPU binding of a process, reported by hwloc.
+    #[error("Failed to retrieve the CPU binding of process {0}")]
+    ProcCpuBindGet(libc::pid_t),
+
+    /// Failure to bind a thread on a given CPU, reported by hwloc.
+    #[error("Failed to bind thread on given CPU")]
+    ThreadCpuBindSet,
+
+    /// Failure to retrieve the CPU binding of a thread, reported by hwloc.
+    #[error("Failed to retrieve the CPU binding of thread")]
+    ThreadCpuBindGet,
+
+    /// Failure to retrieve the last CPU location of the current process or thread, reported by
+    /// hwloc.
+    #[error("Failed to retrieve the last CPU location of the current process or thread")]
+    LastCpuLocation,
+
+    /// Failure to retrieve the last CPU location of a process, reported by hwloc.
+    #[error("Failed to retrieve the last CPU location of process {0}")]
+    ProcLastCpuLocation(libc::pid_t),
+
+    /// Failure to bind the memory of the current process or thread, reported by hwloc.
+    #[error("Failed to set the memory binding of the current process or thread")]
+    MemBindSet,
+
+    /// Failure to retrieve the memory binding of the current process or thread, reported by
+    /// hwloc.
+    #[error("Failed to retrieve the memory binding of the current process or thread")]
+    MemBindGet,
+
+    /// Failure to bind the memory of a process, reported by hwloc.
+    #[error("Failed to set the memory binding of process {0}")]
+    ProcMemBindSet(libc::pid_t),
+
+    /// Failure to retrieve the memory binding of a process, reported by hwloc.
+    #[error("Failed to retrieve the memory binding of process {0}")]
+    ProcMemBindGet(libc::pid_t),
+
+    /// Failure to bind the memory of a memory area, reported by hwloc.
+    #[error("Failed to set the memory binding of a memory area")]
+    AreaMemBindSet,
+
+    /// Failure to retrieve the memory binding of a memory area, reported by hwloc.
+    #[error("Failed to retrieve the memory binding of a memory area")]
+    AreaMemBindGet,
+
+    /// Failure to retrieve the physical location of a memory area, reported by hwloc.
+    #[error("Failed to retrieve the physical location of a memory area")]
+    AreaMemLocation,
+
+    /// Failure to allocate (and possibly bind) memory, reported by hwloc.
+    #[error("Failed to allocate memory")]
+    MemAlloc,
+
+    /// The object has no CPU set (i.e., it is a Misc or an I/O object).
+    #[error("The object has no CPU set")]
+    ObjectWithoutCpuSet,
+
+    /// Failure to spawn a new thread, reported by the OS.
+    #[error("Failed to spawn thread")]
+    ThreadSpawn(#[source] std::io::Error),
+
+    /// Failure to retrieve distance matrices, reported by hwloc.
+    #[error("Failed to retrieve distance matrices")]
+    DistancesGet,
+
+    /// Failure to add a distance matrix, reported by hwloc.
+    #[error("Failed to add distance matrix {0:?}")]
+    DistancesAdd(String),
+
+    /// Failure to remove distance matrices, reported by hwloc.
+    #[error("Failed to remove distance matrices")]
+    DistancesRemove,
+
+    /// Failure to transform a distance matrix, reported by hwloc.
+    #[error("Failed to apply transformation {0:?} to distance matrix")]
+    DistancesTransform(DistancesTransform),
+
+    /// Failure to retrieve a memory attribute, reported by hwloc.
+    #[error("Failed to retrieve memory attribute")]
+    MemAttrGet,
+
+    /// Failure to query the values of a memory attribute, reported by hwloc.
+    #[error("Failed to query memory attribute values")]
+    MemAttrQuery,
+
+    /// Failure to register a memory attribute, reported by hwloc.
+    #[error("Failed to register memory attribute {0:?}")]
+    MemAttrRegister(String),
+
+    /// Failure to set the value of a memory attribute, reported by hwloc.
+    #[error("Failed to set memory attribute value")]
+    MemAttrSet,
+
+    /// There are no objects of the given type to create a thread pool for.
+    #[cfg(feature = "rayon")]
+    #[error("There are no objects of type {0:?} to create a thread pool for")]
+    ThreadPoolEmpty(ObjectType),
+
+    /// Objects of the given type exist at multiple depths (e.g., Groups), so there is no single
+    /// level of objects to create a thread pool for.
+    #[cfg(feature = "rayon")]
+    #[error("Objects of type {0:?} exist at multiple depths")]
+    ThreadPoolMultipleDepths(ObjectType),
+
+    /// Failure to create a thread pool, reported by rayon.
+    #[cfg(feature = "rayon")]
+    #[error("Failed to build thread pool")]
+    ThreadPoolBuild(#[source] rayon::ThreadPoolBuildError),
 }
@@ -1,6 +1,15 @@
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
+
+pub mod binding;
 pub mod bitmap;
+pub mod distances;
 mod error;
+pub mod mem;
+pub mod memattr;
 pub mod object;
+#[cfg(feature = "rayon")]
+pub mod pool;
+pub mod thread;
 pub mod topology;
 pub mod types;
 
@@ -50,6 +59,26 @@ mod tests {
         ObjectType::Die,
     ];
 
+    /// A single-package machine with one NUMA node and two dual-threaded cores.
+    const XML_1P_2C_4PU: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE topology SYSTEM "hwloc2.dtd">
+<topology version="2.0">
+  <object type="Machine" os_index="0" cpuset="0x0000000f" complete_cpuset="0x0000000f" allowed_cpuset="0x0000000f" nodeset="0x00000001" complete_nodeset="0x00000001" allowed_nodeset="0x00000001" gp_index="1">
+    <object type="Package" os_index="0" cpuset="0x0000000f" complete_cpuset="0x0000000f" nodeset="0x00000001" complete_nodeset="0x00000001" gp_index="2">
+      <object type="NUMANode" os_index="0" cpuset="0x0000000f" complete_cpuset="0x0000000f" nodeset="0x00000001" complete_nodeset="0x00000001" gp_index="3" local_memory="1073741824"/>
+      <object type="Core" os_index="0" cpuset="0x00000003" complete_cpuset="0x00000003" nodeset="0x00000001" complete_nodeset="0x00000001" gp_index="4">
+        <object type="PU" os_index="0" cpuset="0x00000001" complete_cpuset="0x00000001" nodeset="0x00000001" complete_nodeset="0x00000001" gp_index="5"/>
+        <object type="PU" os_index="1" cpuset="0x00000002" complete_cpuset="0x00000002" nodeset="0x00000001" complete_nodeset="0x00000001" gp_index="6"/>
+      </object>
+      <object type="Core" os_index="1" cpuset="0x0000000c" complete_cpuset="0x0000000c" nodeset="0x00000001" complete_nodeset="0x00000001" gp_index="7">
+        <object type="PU" os_index="2" cpuset="0x00000004" complete_cpuset="0x00000004" nodeset="0x00000001" complete_nodeset="0x00000001" gp_index="8"/>
+        <object type="PU" os_index="3" cpuset="0x00000008" complete_cpuset="0x00000008" nodeset="0x00000001" complete_nodeset="0x00000001" gp_index="9"/>
+      </object>
+    </object>
+  </object>
+</topology>
+"#;
+
     fn all_filters(topo: &Topology) -> Result<HashMap<ObjectType, topology::Filter>> {
         let mut ret = HashMap::with_capacity(ALL_OBJECT_TYPES.len());
         for ot in ALL_OBJECT_TYPES {
@@ -87,6 +116,209 @@ mod tests {
         print_topo(&topo)
     }
 
+    #[test]
+    fn build_topology_for_pid() -> Result<()> {
+        // The parent process always exists, and on Linux its view may be discovered from outside.
+        let pid = unsafe { libc::getppid() };
+        let topo = Topology::builder()
+            .with_context(|| "failed to create the TopologyBuilder")?
+            .for_pid(pid)
+            .with_context(|| format!("failed to set the target process to {}", pid))?
+            .flags(topology::Flags::RESTRICT_TO_CPUBINDING)
+            .with_context(|| "failed to set RESTRICT_TO_CPUBINDING flag")?
+            .build()
+            .with_context(|| "failed to build the Topology")?;
+        print_topo(&topo)
+    }
+
+    #[test]
+    fn build_topology_components() -> Result<()> {
+        let topo = Topology::builder()
+            .with_context(|| "failed to create the TopologyBuilder")?
+            .exclude_component("linuxio")
+            .with_context(|| "failed to exclude the I/O discovery component")?
+            .build()
+            .with_context(|| "failed to build the Topology")?;
+        assert!(topo.nbobjs_by_type(ObjectType::PU) > 0);
+
+        let topo = Topology::builder()?
+            .only_components(&["linux", "x86", "noos"])
+            .with_context(|| "failed to restrict the discovery components")?
+            .build()
+            .with_context(|| "failed to build the Topology")?;
+        assert!(topo.nbobjs_by_type(ObjectType::PU) > 0);
+        Ok(())
+    }
+
+    #[test]
+    fn exclude_unknown_component() {
+        assert!(matches!(
+            Topology::builder().and_then(|builder| builder.exclude_component("no-such-component")),
+            Err(Error::TopologySetComponents(_))
+        ));
+    }
+
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+    /////
+    /////  Topologies from XML
+    /////
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+
+    fn assert_1p_2c_4pu(topo: &Topology) {
+        assert_eq!(1, topo.nbobjs_by_type(ObjectType::Package));
+        assert_eq!(1, topo.nbobjs_by_type(ObjectType::NumaNode));
+        assert_eq!(2, topo.nbobjs_by_type(ObjectType::Core));
+        assert_eq!(4, topo.nbobjs_by_type(ObjectType::PU));
+    }
+
+    #[test]
+    fn build_from_xml_str() -> Result<()> {
+        let topo = TopologyBuilder::from_xml_str(XML_1P_2C_4PU)
+            .with_context(|| "failed to create the TopologyBuilder from XML")?
+            .build()
+            .with_context(|| "failed to build the Topology")?;
+        print_topo(&topo)?;
+        assert!(!topo.is_this_system());
+        assert_1p_2c_4pu(&topo);
+        Ok(())
+    }
+
+    #[test]
+    fn build_from_xml_file() -> Result<()> {
+        let path = std::env::temp_dir().join(format!("hwloc2-rs-{}.xml", std::process::id()));
+        std::fs::write(&path, XML_1P_2C_4PU).with_context(|| "failed to write XML file")?;
+        let topo = TopologyBuilder::from_xml_file(&path)
+            .with_context(|| "failed to create the TopologyBuilder from XML file")?
+            .build();
+        std::fs::remove_file(&path).with_context(|| "failed to remove XML file")?;
+        let topo = topo.with_context(|| "failed to build the Topology")?;
+        assert_1p_2c_4pu(&topo);
+        Ok(())
+    }
+
+    #[test]
+    fn build_from_invalid_xml() {
+        // Depending on the XML backend of hwloc (libxml2 or its minimal built-in parser), malformed
+        // XML is either rejected right away, or only once the topology is built.
+        assert!(matches!(
+            TopologyBuilder::from_xml_str("<topology").and_then(|builder| builder.build()),
+            Err(Error::TopologySetXmlBuffer | Error::TopologyLoadXml)
+        ));
+        assert!(matches!(
+            TopologyBuilder::from_xml_file("/nonexistent/hwloc2-rs.xml"),
+            Err(Error::TopologySetXml(_))
+        ));
+    }
+
+    #[test]
+    fn xml_string_roundtrip() -> Result<()> {
+        let topo = TopologyBuilder::from_synthetic("pack:2 numa:1 core:2 pu:2")?.build()?;
+        for flags in [
+            topology::XmlExportFlags::empty(),
+            topology::XmlExportFlags::V1,
+        ] {
+            let xml = topo
+                .to_xml_string(flags)
+                .with_context(|| format!("failed to export the Topology to XML ({:?})", flags))?;
+            let reloaded = TopologyBuilder::from_xml_str(&xml)
+                .with_context(|| "failed to create the TopologyBuilder from exported XML")?
+                .build()
+                .with_context(|| "failed to build the Topology from exported XML")?;
+            assert_eq!(2, reloaded.nbobjs_by_type(ObjectType::Package));
+            assert_eq!(2, reloaded.nbobjs_by_type(ObjectType::NumaNode));
+            assert_eq!(8, reloaded.nbobjs_by_type(ObjectType::PU));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn xml_file_roundtrip() -> Result<()> {
+        let topo = TopologyBuilder::from_xml_str(XML_1P_2C_4PU)?.build()?;
+        let path =
+            std::env::temp_dir().join(format!("hwloc2-rs-export-{}.xml", std::process::id()));
+        topo.export_xml(&path, topology::XmlExportFlags::default())
+            .with_context(|| "failed to export the Topology to XML file")?;
+        let reloaded = TopologyBuilder::from_xml_file(&path).and_then(TopologyBuilder::build);
+        std::fs::remove_file(&path).with_context(|| "failed to remove XML file")?;
+        assert_1p_2c_4pu(&reloaded.with_context(|| "failed to reload the exported Topology")?);
+        Ok(())
+    }
+
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+    /////
+    /////  Synthetic topologies
+    /////
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+
+    #[test]
+    fn build_from_synthetic() -> Result<()> {
+        let topo = TopologyBuilder::from_synthetic("pack:2 numa:1 core:8 pu:2")
+            .with_context(|| "failed to create the TopologyBuilder from synthetic description")?
+            .build()
+            .with_context(|| "failed to build the Topology")?;
+        print_topo(&topo)?;
+        assert_eq!(2, topo.nbobjs_by_type(ObjectType::Package));
+        assert_eq!(2, topo.nbobjs_by_type(ObjectType::NumaNode));
+        assert_eq!(16, topo.nbobjs_by_type(ObjectType::Core));
+        assert_eq!(32, topo.nbobjs_by_type(ObjectType::PU));
+        Ok(())
+    }
+
+    #[test]
+    fn build_from_synthetic_description() -> Result<()> {
+        let desc = topology::SyntheticDescription::new()
+            .objects(ObjectType::Package, 2)
+            .level(topology::SyntheticLevel::new(ObjectType::NumaNode, 1).memory(1 << 30))
+            .level(topology::SyntheticLevel::new(ObjectType::L3Cache, 1).cache_size(8 << 20))
+            .objects(ObjectType::Core, 4)
+            .objects(ObjectType::PU, 1);
+        let topo = TopologyBuilder::from_synthetic_description(&desc)
+            .with_context(|| "failed to create the TopologyBuilder from synthetic description")?
+            .build()
+            .with_context(|| "failed to build the Topology")?;
+        assert_eq!(2, topo.nbobjs_by_type(ObjectType::NumaNode));
+        assert_eq!(2, topo.nbobjs_by_type(ObjectType::L3Cache));
+        assert_eq!(8, topo.nbobjs_by_type(ObjectType::PU));
+
+        let numa = topo
+            .object_by_type(ObjectType::NumaNode, 0)
+            .expect("failed to get the first NUMA node");
+        match numa.attributes() {
+            Some(object::Attributes::NumaNode(attrs)) => assert_eq!(1 << 30, attrs.local_memory()),
+            attrs => panic!("unexpected NUMA node attributes: {:?}", attrs),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn synthetic_roundtrip() -> Result<()> {
+        use topology::SyntheticExportFlags;
+
+        let topo = TopologyBuilder::from_synthetic("pack:2 numa:1 core:4 pu:2")?.build()?;
+        let desc = topo
+            .to_synthetic(SyntheticExportFlags::empty())
+            .with_context(|| "failed to export the Topology as synthetic")?;
+        let reloaded = TopologyBuilder::from_synthetic(&desc)?.build()?;
+        assert_eq!(2, reloaded.nbobjs_by_type(ObjectType::Package));
+        assert_eq!(2, reloaded.nbobjs_by_type(ObjectType::NumaNode));
+        assert_eq!(8, reloaded.nbobjs_by_type(ObjectType::Core));
+        assert_eq!(16, reloaded.nbobjs_by_type(ObjectType::PU));
+        assert_eq!(desc, reloaded.to_synthetic(SyntheticExportFlags::empty())?);
+
+        let no_mem = topo
+            .to_synthetic(SyntheticExportFlags::IGNORE_MEMORY | SyntheticExportFlags::NO_ATTRS)?;
+        assert!(!no_mem.contains("NUMA"));
+        Ok(())
+    }
+
+    #[test]
+    fn build_from_invalid_synthetic() {
+        assert!(matches!(
+            TopologyBuilder::from_synthetic("pack:2 foo:3 pu:1"),
+            Err(Error::TopologySetSynthetic(_))
+        ));
+    }
+
     #[test]
     fn get_root_obj() -> Result<()> {
         let topo = Topology::builder()
@@ -130,6 +362,1225 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn object_by_os_index() -> Result<()> {
+        let topo = TopologyBuilder::from_synthetic("pack:2 numa:1 core:2 pu:2")?.build()?;
+        for (obj_type, nbobjs) in [(ObjectType::PU, 8), (ObjectType::NumaNode, 2)] {
+            assert_eq!(nbobjs, topo.nbobjs_by_type(obj_type));
+            for idx in 0..nbobjs as u32 {
+                let obj = topo.object_by_type(obj_type, idx).unwrap();
+                let found = match obj_type {
+                    ObjectType::PU => topo.pu_object_by_os_index(obj.os_index()),
+                    _ => topo.numanode_object_by_os_index(obj.os_index()),
+                }
+                .with_context(|| format!("failed to find {} by OS index", obj))?;
+                assert_eq!(obj.os_index(), found.os_index());
+                assert_eq!(obj.logical_index(), found.logical_index());
+            }
+        }
+        assert!(topo.pu_object_by_os_index(8).is_none());
+        assert!(topo.numanode_object_by_os_index(2).is_none());
+        Ok(())
+    }
+
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+    /////
+    /////  Duplicating topologies
+    /////
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+
+    #[test]
+    fn clone_topology() -> Result<()> {
+        let topo = TopologyBuilder::from_synthetic("pack:2 numa:1 core:4 pu:2")?.build()?;
+        let dup = topo
+            .try_clone()
+            .with_context(|| "failed to duplicate the Topology")?;
+        let expected = topo.to_synthetic(topology::SyntheticExportFlags::empty())?;
+        drop(topo);
+        assert_eq!(16, dup.nbobjs_by_type(ObjectType::PU));
+        assert_eq!(
+            expected,
+            dup.try_clone()?
+                .to_synthetic(topology::SyntheticExportFlags::empty())?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn clone_topology_builder() -> Result<()> {
+        let builder = TopologyBuilder::from_xml_str(XML_1P_2C_4PU)?
+            .type_filter(ObjectType::Core, topology::Filter::KeepNone)
+            .with_context(|| "failed to set the Core filter")?;
+        let dup = builder
+            .try_clone()
+            .with_context(|| "failed to clone the TopologyBuilder")?;
+        let another = dup.try_clone()?;
+
+        for topo in [builder.build()?, dup.build()?, another.build()?] {
+            assert_eq!(1, topo.nbobjs_by_type(ObjectType::Package));
+            assert_eq!(0, topo.nbobjs_by_type(ObjectType::Core));
+            assert_eq!(4, topo.nbobjs_by_type(ObjectType::PU));
+        }
+        Ok(())
+    }
+
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+    /////
+    /////  Restricting topologies
+    /////
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+
+    #[test]
+    fn restrict_by_cpuset() -> Result<()> {
+        let mut topo = TopologyBuilder::from_synthetic("pack:2 numa:1 core:4 pu:2")?.build()?;
+        let cpuset: bitmap::CpuSet = (4..12).collect();
+        topo.restrict(&cpuset, topology::RestrictFlags::empty())
+            .with_context(|| "failed to restrict the Topology")?;
+
+        // Cores #2-#3 of the first package and #0-#1 of the second one are kept.
+        assert_eq!(2, topo.nbobjs_by_type(ObjectType::Package));
+        assert_eq!(4, topo.nbobjs_by_type(ObjectType::Core));
+        assert_eq!(8, topo.nbobjs_by_type(ObjectType::PU));
+        let first_pu = topo.object_by_type(ObjectType::PU, 0).unwrap();
+        assert_eq!(0, first_pu.logical_index());
+        assert_eq!(4, first_pu.os_index());
+        assert_eq!(
+            Some(cpuset),
+            topo.root_object().and_then(|root| root.cpuset())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn restrict_by_nodeset() -> Result<()> {
+        let mut topo = TopologyBuilder::from_synthetic("pack:2 numa:1 core:4 pu:2")?.build()?;
+        let nodeset: bitmap::NodeSet = std::iter::once(1).collect();
+        topo.restrict(
+            &nodeset,
+            topology::RestrictFlags::BYNODESET | topology::RestrictFlags::REMOVE_MEMLESS,
+        )
+        .with_context(|| "failed to restrict the Topology")?;
+
+        assert_eq!(1, topo.nbobjs_by_type(ObjectType::Package));
+        assert_eq!(1, topo.nbobjs_by_type(ObjectType::NumaNode));
+        let node = topo.object_by_type(ObjectType::NumaNode, 0).unwrap();
+        assert_eq!(1, node.os_index());
+        Ok(())
+    }
+
+    #[test]
+    fn restrict_then_share() -> Result<()> {
+        let mut topo = TopologyBuilder::from_synthetic("pack:2 numa:1 core:4 pu:2")?.build()?;
+        let cpuset: bitmap::CpuSet = (0..8).collect();
+        topo.restrict(&cpuset, topology::RestrictFlags::REMOVE_CPULESS)?;
+
+        // The caches that restricting invalidated are refreshed before the topology is shared.
+        let topo = std::sync::Arc::new(topo);
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let topo = std::sync::Arc::clone(&topo);
+                std::thread::spawn(move || -> Result<usize> {
+                    topo.distances(distances::DistancesKind::empty())?;
+                    Ok(topo
+                        .memattr(memattr::MemAttrId::LOCALITY)?
+                        .targets(None)?
+                        .len())
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(1, handle.join().unwrap()?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn restrict_invalid() -> Result<()> {
+        let mut topo = TopologyBuilder::from_synthetic("pack:2 numa:1 core:4 pu:2")?.build()?;
+        let cpuset: bitmap::CpuSet = (0..4).collect();
+        assert!(matches!(
+            topo.restrict(
+                &cpuset,
+                topology::RestrictFlags::REMOVE_CPULESS | topology::RestrictFlags::BYNODESET,
+            ),
+            Err(Error::TopologyRestrict(_))
+        ));
+        assert_eq!(16, topo.nbobjs_by_type(ObjectType::PU));
+        Ok(())
+    }
+
+    #[test]
+    fn allow_custom_sets() -> Result<()> {
+        let mut topo = TopologyBuilder::from_synthetic("pack:2 numa:1 core:4 pu:2")?
+            .flags(topology::Flags::INCLUDE_DISALLOWED)?
+            .build()?;
+        let cpuset: bitmap::CpuSet = (0..4).collect();
+        let nodeset: bitmap::NodeSet = std::iter::once(0).collect();
+        topo.allow(topology::Allow::Custom {
+            cpuset: Some(&cpuset),
+            nodeset: Some(&nodeset),
+        })
+        .with_context(|| "failed to allow custom sets")?;
+        assert_eq!(cpuset, topo.allowed_cpuset()?);
+        assert_eq!(nodeset, topo.allowed_nodeset()?);
+        // Objects are not modified.
+        assert_eq!(16, topo.nbobjs_by_type(ObjectType::PU));
+
+        topo.allow(topology::Allow::All)
+            .with_context(|| "failed to allow all objects")?;
+        assert_eq!(topo.topology_cpuset()?, topo.allowed_cpuset()?);
+        assert_eq!(topo.topology_nodeset()?, topo.allowed_nodeset()?);
+        Ok(())
+    }
+
+    #[test]
+    fn allow_invalid() -> Result<()> {
+        let mut topo = TopologyBuilder::from_synthetic("pack:2 numa:1 core:4 pu:2")?.build()?;
+        // `Flags::INCLUDE_DISALLOWED` was not set.
+        assert!(matches!(
+            topo.allow(topology::Allow::All),
+            Err(Error::TopologyAllow)
+        ));
+
+        let mut topo = TopologyBuilder::from_synthetic("pack:2 numa:1 core:4 pu:2")?
+            .flags(topology::Flags::INCLUDE_DISALLOWED)?
+            .build()?;
+        assert!(matches!(
+            topo.allow(topology::Allow::Custom {
+                cpuset: None,
+                nodeset: None
+            }),
+            Err(Error::TopologyAllow)
+        ));
+        Ok(())
+    }
+
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+    /////
+    /////  CPU binding
+    /////
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+
+    /// The first PU that the process is allowed to run on, for tests that bind to a single PU.
+    fn first_allowed_pu(topo: &Topology) -> Result<Object<'_>> {
+        let os_index = topo
+            .allowed_cpuset()?
+            .first()
+            .with_context(|| "there are no allowed PUs")?;
+        topo.pu_object_by_os_index(os_index as u32)
+            .with_context(|| "failed to retrieve the first allowed PU")
+    }
+
+    #[test]
+    fn cpubind_roundtrip() -> Result<()> {
+        let topo = Topology::builder()?.build()?;
+        if !topo.support().cpubind().get_thisthread_cpubind()
+            || !topo.support().cpubind().set_thisthread_cpubind()
+        {
+            eprintln!("** Thread binding is not supported; skipping");
+            return Ok(());
+        }
+
+        let initial = topo
+            .cpubind(binding::CpuBindFlags::THREAD)
+            .with_context(|| "failed to get the initial binding of current thread")?;
+        let pu = first_allowed_pu(&topo)?
+            .cpuset()
+            .expect("failed to retrieve the CPU set of the first allowed PU");
+        topo.set_cpubind(&pu, binding::CpuBindFlags::THREAD)
+            .with_context(|| "failed to bind current thread")?;
+        assert_eq!(pu, topo.cpubind(binding::CpuBindFlags::THREAD)?);
+
+        topo.set_cpubind(&initial, binding::CpuBindFlags::THREAD)
+            .with_context(|| "failed to restore the initial binding of current thread")?;
+        assert_eq!(initial, topo.cpubind(binding::CpuBindFlags::THREAD)?);
+        Ok(())
+    }
+
+    #[test]
+    fn bind_scoped() -> Result<()> {
+        let topo = Topology::builder()?.build()?;
+        if !topo.support().cpubind().get_thisthread_cpubind()
+            || !topo.support().cpubind().set_thisthread_cpubind()
+        {
+            eprintln!("** Thread binding is not supported; skipping");
+            return Ok(());
+        }
+
+        let initial = topo.cpubind(binding::CpuBindFlags::THREAD)?;
+        let pu = first_allowed_pu(&topo)?
+            .cpuset()
+            .expect("failed to retrieve the CPU set of the first allowed PU");
+        {
+            let guard = topo
+                .bind_scoped(&pu, binding::CpuBindFlags::THREAD)
+                .with_context(|| "failed to temporarily bind current thread")?;
+            assert_eq!(&initial, guard.previous());
+            assert_eq!(pu, topo.cpubind(binding::CpuBindFlags::THREAD)?);
+        }
+        assert_eq!(initial, topo.cpubind(binding::CpuBindFlags::THREAD)?);
+
+        let guard = topo.bind_scoped(&pu, binding::CpuBindFlags::THREAD)?;
+        guard
+            .restore()
+            .with_context(|| "failed to restore the initial binding of current thread")?;
+        assert_eq!(initial, topo.cpubind(binding::CpuBindFlags::THREAD)?);
+        Ok(())
+    }
+
+    #[test]
+    fn proc_cpubind() -> Result<()> {
+        let topo = Topology::builder()?.build()?;
+        if !topo.support().cpubind().get_proc_cpubind()
+            || !topo.support().cpubind().set_proc_cpubind()
+        {
+            eprintln!("** Process binding is not supported; skipping");
+            return Ok(());
+        }
+
+        // Only read the binding of the current process, since changing it would also rebind the
+        // threads of concurrent tests; a child process is bound instead.
+        let initial = topo
+            .proc_cpubind(
+                std::process::id() as libc::pid_t,
+                binding::CpuBindFlags::empty(),
+            )
+            .with_context(|| "failed to get the binding of current process")?;
+        assert!(!initial.is_empty());
+
+        let pu = first_allowed_pu(&topo)?.cpuset().unwrap();
+        let mut child = std::process::Command::new("sleep")
+            .arg("60")
+            .spawn()
+            .with_context(|| "failed to spawn a child process")?;
+        let pid = child.id() as libc::pid_t;
+        let bound = topo
+            .set_proc_cpubind(pid, &pu, binding::CpuBindFlags::empty())
+            .with_context(|| "failed to bind the child process")
+            .and_then(|()| {
+                topo.proc_cpubind(pid, binding::CpuBindFlags::empty())
+                    .with_context(|| "failed to get the binding of the child process")
+            });
+        child.kill()?;
+        child.wait()?;
+        assert_eq!(pu, bound?);
+        Ok(())
+    }
+
+    #[test]
+    fn thread_cpubind() -> Result<()> {
+        let topo = Topology::builder()?.build()?;
+        if !topo.support().cpubind().get_thread_cpubind()
+            || !topo.support().cpubind().set_thread_cpubind()
+        {
+            eprintln!("** Thread binding is not supported; skipping");
+            return Ok(());
+        }
+        let pu = first_allowed_pu(&topo)?
+            .cpuset()
+            .expect("failed to retrieve the CPU set of the first allowed PU");
+
+        // Keep the worker alive until its binding has been checked.
+        let (tx, rx) = std::sync::mpsc::channel::<()>();
+        let worker = std::thread::spawn(move || rx.recv());
+        let thread = binding::ThreadHandle::from(&worker);
+        topo.set_thread_cpubind(thread, &pu, binding::CpuBindFlags::empty())
+            .with_context(|| "failed to bind the worker thread")?;
+        let bound = topo
+            .thread_cpubind(thread, binding::CpuBindFlags::empty())
+            .with_context(|| "failed to get the binding of the worker thread")?;
+        drop(tx);
+        worker.join().expect("worker thread panicked").ok();
+        assert_eq!(pu, bound);
+        Ok(())
+    }
+
+    #[test]
+    fn last_cpu_location() -> Result<()> {
+        let topo = Topology::builder()?.build()?;
+        if !topo.support().cpubind().get_thisthread_last_cpu_location()
+            || !topo.support().cpubind().get_proc_last_cpu_location()
+        {
+            eprintln!("** Last CPU location is not supported; skipping");
+            return Ok(());
+        }
+
+        let location = topo
+            .last_cpu_location(binding::CpuBindFlags::THREAD)
+            .with_context(|| "failed to get the last CPU location of current thread")?;
+        assert_eq!(1, location.weight());
+        let pu = topo
+            .last_pu_location(binding::CpuBindFlags::THREAD)?
+            .expect("failed to map the last CPU location to a PU");
+        assert_eq!(ObjectType::PU, pu.object_type());
+        assert!(topo.allowed_cpuset()?.is_set(pu.os_index()));
+
+        let pid = std::process::id() as libc::pid_t;
+        let location = topo
+            .proc_last_cpu_location(pid, binding::CpuBindFlags::empty())
+            .with_context(|| "failed to get the last CPU location of current process")?;
+        assert!(!location.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn objects_by_os_index() -> Result<()> {
+        let topo = TopologyBuilder::from_synthetic("pack:2 numa:1 core:4 pu:2")?.build()?;
+        for os_index in 0..16 {
+            let pu = topo.pu_object_by_os_index(os_index).unwrap();
+            assert_eq!(ObjectType::PU, pu.object_type());
+            assert_eq!(os_index, pu.os_index());
+        }
+        assert!(topo.pu_object_by_os_index(16).is_none());
+        let node = topo.numanode_object_by_os_index(1).unwrap();
+        assert_eq!(ObjectType::NumaNode, node.object_type());
+        assert_eq!(1, node.os_index());
+        assert!(topo.numanode_object_by_os_index(2).is_none());
+        Ok(())
+    }
+
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+    /////
+    /////  Memory binding
+    /////
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+
+    #[test]
+    fn membind_roundtrip() -> Result<()> {
+        use binding::{MemBindFlags, MemBindPolicy, MemBindSet};
+
+        let topo = Topology::builder()?.build()?;
+        let support = topo.support().membind();
+        if !support.set_thisthread_membind()
+            || !support.get_thisthread_membind()
+            || !support.bind_membind()
+        {
+            eprintln!("** Thread memory binding is not supported; skipping");
+            return Ok(());
+        }
+
+        let nodeset = topo.topology_nodeset()?;
+        let node: bitmap::NodeSet = std::iter::once(nodeset.first().unwrap() as u32).collect();
+        topo.set_membind(
+            MemBindSet::NodeSet(&node),
+            MemBindPolicy::Bind,
+            MemBindFlags::THREAD,
+        )
+        .with_context(|| "failed to bind the memory of current thread")?;
+        let (bound, policy) = topo
+            .membind(MemBindFlags::THREAD)
+            .with_context(|| "failed to get the memory binding of current thread")?;
+        assert_eq!(node, bound);
+        assert_eq!(MemBindPolicy::Bind, policy);
+
+        // Binding by CPU set binds to the NUMA nodes that are local to the given PUs.
+        let cpuset = topo.cpuset_from_nodeset(node.clone())?;
+        topo.set_membind(
+            MemBindSet::CpuSet(&cpuset),
+            MemBindPolicy::Bind,
+            MemBindFlags::THREAD,
+        )?;
+        assert_eq!(node, topo.membind(MemBindFlags::THREAD)?.0);
+
+        topo.set_membind(
+            MemBindSet::NodeSet(&nodeset),
+            MemBindPolicy::Default,
+            MemBindFlags::THREAD,
+        )
+        .with_context(|| "failed to reset the memory binding of current thread")?;
+        Ok(())
+    }
+
+    #[test]
+    fn membind_scoped() -> Result<()> {
+        use binding::{MemBindFlags, MemBindPolicy, MemBindSet};
+
+        let topo = Topology::builder()?.build()?;
+        let support = topo.support().membind();
+        if !support.set_thisthread_membind()
+            || !support.get_thisthread_membind()
+            || !support.bind_membind()
+        {
+            eprintln!("** Thread memory binding is not supported; skipping");
+            return Ok(());
+        }
+
+        let initial = topo.membind(MemBindFlags::THREAD)?;
+        let nodeset = topo.topology_nodeset()?;
+        let node: bitmap::NodeSet = std::iter::once(nodeset.first().unwrap() as u32).collect();
+        {
+            let guard = topo
+                .membind_scoped(
+                    MemBindSet::NodeSet(&node),
+                    MemBindPolicy::Bind,
+                    MemBindFlags::THREAD,
+                )
+                .with_context(|| "failed to temporarily bind the memory of current thread")?;
+            assert_eq!((&initial.0, initial.1), guard.previous());
+            assert_eq!(
+                (node.clone(), MemBindPolicy::Bind),
+                topo.membind(MemBindFlags::THREAD)?
+            );
+        }
+        assert_eq!(initial, topo.membind(MemBindFlags::THREAD)?);
+        Ok(())
+    }
+
+    #[test]
+    fn proc_membind() -> Result<()> {
+        use binding::{MemBindFlags, MemBindPolicy, MemBindSet};
+
+        let topo = Topology::builder()?.build()?;
+        let support = topo.support().membind();
+        if !support.set_proc_membind() || !support.get_proc_membind() {
+            eprintln!("** Process memory binding is not supported; skipping");
+            return Ok(());
+        }
+
+        // Only read the memory binding of the current process, since changing it would also
+        // affect concurrent tests; a child process is bound instead.
+        let (initial, _) = topo
+            .proc_membind(std::process::id() as libc::pid_t, MemBindFlags::empty())
+            .with_context(|| "failed to get the memory binding of current process")?;
+        assert!(!initial.is_empty());
+
+        let nodeset = first_allowed_pu(&topo)?.nodeset().unwrap();
+        let mut child = std::process::Command::new("sleep")
+            .arg("60")
+            .spawn()
+            .with_context(|| "failed to spawn a child process")?;
+        let pid = child.id() as libc::pid_t;
+        let bound = topo
+            .set_proc_membind(
+                pid,
+                MemBindSet::NodeSet(&nodeset),
+                MemBindPolicy::Bind,
+                MemBindFlags::empty(),
+            )
+            .with_context(|| "failed to bind the memory of the child process")
+            .and_then(|()| {
+                topo.proc_membind(pid, MemBindFlags::empty())
+                    .with_context(|| "failed to get the memory binding of the child process")
+            });
+        child.kill()?;
+        child.wait()?;
+        assert_eq!((nodeset, MemBindPolicy::Bind), bound?);
+        Ok(())
+    }
+
+    #[test]
+    fn set_membind_mixed() -> Result<()> {
+        use binding::{MemBindFlags, MemBindPolicy, MemBindSet};
+
+        let topo = Topology::builder()?.build()?;
+        assert!(matches!(
+            topo.set_membind(
+                MemBindSet::NodeSet(&topo.topology_nodeset()?),
+                MemBindPolicy::Mixed,
+                MemBindFlags::empty()
+            ),
+            Err(Error::MemBindSet)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn area_membind() -> Result<()> {
+        use binding::{MemBindFlags, MemBindPolicy, MemBindSet};
+
+        let topo = Topology::builder()?.build()?;
+        let support = topo.support().membind();
+        if !support.set_area_membind() || !support.get_area_membind() || !support.bind_membind() {
+            eprintln!("** Area memory binding is not supported; skipping");
+            return Ok(());
+        }
+
+        let nodeset = topo.topology_nodeset()?;
+        let node: bitmap::NodeSet = std::iter::once(nodeset.first().unwrap() as u32).collect();
+        let mut area = vec![0u8; 1 << 20];
+        topo.set_area_membind(
+            &mut area,
+            MemBindSet::NodeSet(&node),
+            MemBindPolicy::Bind,
+            MemBindFlags::MIGRATE,
+        )
+        .with_context(|| "failed to bind the memory of the area")?;
+        let (bound, policy) = topo
+            .area_membind(&area, MemBindFlags::empty())
+            .with_context(|| "failed to get the memory binding of the area")?;
+        assert_eq!(node, bound);
+        assert_eq!(MemBindPolicy::Bind, policy);
+
+        if support.get_area_memlocation() {
+            area.iter_mut().for_each(|b| *b = 1);
+            let location = topo
+                .area_memlocation(&area, MemBindFlags::empty())
+                .with_context(|| "failed to get the physical location of the area")?;
+            assert_eq!(node, location);
+        }
+
+        assert!(topo
+            .set_area_membind::<u8>(
+                &mut [],
+                MemBindSet::NodeSet(&node),
+                MemBindPolicy::Bind,
+                MemBindFlags::empty()
+            )
+            .is_ok());
+        assert!(matches!(
+            topo.area_membind::<u8>(&[], MemBindFlags::empty()),
+            Err(Error::AreaMemBindGet)
+        ));
+        Ok(())
+    }
+
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+    /////
+    /////  Memory allocation
+    /////
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+
+    #[test]
+    fn numa_buffer() -> Result<()> {
+        use mem::NumaBuffer;
+
+        let topo = Topology::builder()?.build()?;
+        let mut buf = NumaBuffer::from_fn(&topo, 4096, |i| i as u64)
+            .with_context(|| "failed to allocate a NumaBuffer")?;
+        assert_eq!(4096, buf.len());
+        assert_eq!(0, buf.as_ptr() as usize % 4096);
+        buf[0] = 42;
+        assert_eq!(42 + (1..4096).sum::<u64>(), buf.iter().sum::<u64>());
+
+        let strings = NumaBuffer::from_elem(&topo, 3, String::from("hwloc"))?;
+        assert_eq!(["hwloc", "hwloc", "hwloc"], *strings);
+        assert!(NumaBuffer::from_elem(&topo, 0, 0u8)?.is_empty());
+        assert_eq!(1 << 20, NumaBuffer::from_elem(&topo, 1 << 20, ())?.len());
+        assert!(matches!(
+            NumaBuffer::from_elem(&topo, usize::MAX, 0u64),
+            Err(Error::MemAlloc)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn numa_buffer_membind() -> Result<()> {
+        use binding::{MemBindFlags, MemBindPolicy, MemBindSet};
+        use mem::NumaBuffer;
+
+        let topo = Topology::builder()?.build()?;
+        let support = topo.support().membind();
+        if !support.alloc_membind() || !support.bind_membind() {
+            eprintln!("** Memory allocation binding is not supported; skipping");
+            return Ok(());
+        }
+
+        let nodeset = topo.topology_nodeset()?;
+        let node: bitmap::NodeSet = std::iter::once(nodeset.first().unwrap() as u32).collect();
+        let buf = NumaBuffer::from_elem_membind(
+            &topo,
+            1 << 16,
+            MemBindSet::NodeSet(&node),
+            MemBindPolicy::Bind,
+            MemBindFlags::STRICT,
+            1u32,
+        )
+        .with_context(|| "failed to allocate a bound NumaBuffer")?;
+        assert_eq!(1 << 16, buf.iter().sum::<u32>());
+        if support.get_area_membind() {
+            let (bound, policy) = topo.area_membind(&buf, MemBindFlags::empty())?;
+            assert_eq!(node, bound);
+            assert_eq!(MemBindPolicy::Bind, policy);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn numa_allocator() -> Result<()> {
+        use std::alloc::{GlobalAlloc, Layout};
+
+        use binding::MemBindFlags;
+        use mem::NumaAllocator;
+
+        let topo = Topology::builder()?.build()?;
+        let nodeset = topo.topology_nodeset()?;
+        let alloc = NumaAllocator::new(&topo, nodeset.clone());
+        assert_eq!(&nodeset, alloc.nodeset());
+
+        let layouts = [
+            (1, 1),
+            (8, 8),
+            (24, 8),
+            (100, 64),
+            (2048, 2048),
+            (3000, 8),
+            (1 << 20, 64),
+        ];
+        let mut ptrs = Vec::new();
+        for _ in 0..100 {
+            for &(size, align) in &layouts {
+                let layout = Layout::from_size_align(size, align)?;
+                // SAFETY: `layout` has a non-zero size.
+                let ptr = unsafe { alloc.alloc(layout) };
+                assert!(!ptr.is_null());
+                assert_eq!(0, ptr as usize % align);
+                // SAFETY: `ptr` points to `size` bytes, which are exclusively owned by the test.
+                unsafe { ptr.write_bytes(0xAB, size) };
+                ptrs.push((ptr, layout));
+            }
+        }
+        if topo.support().membind().get_area_membind() {
+            let (ptr, layout) = ptrs[layouts.len() - 1];
+            // SAFETY: `ptr` points to `layout.size()` initialized bytes.
+            let area = unsafe { std::slice::from_raw_parts(ptr, layout.size()) };
+            assert_eq!(nodeset, topo.area_membind(area, MemBindFlags::empty())?.0);
+        }
+        for (ptr, layout) in ptrs {
+            // SAFETY: `ptr` was allocated by `alloc` with the same layout, and it is freed once.
+            unsafe { alloc.dealloc(ptr, layout) };
+        }
+
+        let huge = Layout::from_size_align(64, 1 << 20)?;
+        // SAFETY: `huge` has a non-zero size.
+        assert!(unsafe { alloc.alloc(huge) }.is_null());
+        Ok(())
+    }
+
+    #[cfg(feature = "allocator_api")]
+    #[test]
+    fn numa_allocator_collections() -> Result<()> {
+        use mem::NumaAllocator;
+
+        let topo = Topology::builder()?.build()?;
+        let alloc = NumaAllocator::new(&topo, topo.topology_nodeset()?);
+        let mut v = Vec::new_in(&alloc);
+        v.extend(0..100_000u64);
+        assert_eq!((0..100_000u64).sum::<u64>(), v.iter().sum::<u64>());
+        let b = Box::new_in([0u8; 16], &alloc);
+        assert_eq!([0u8; 16], *b);
+        let empty: Vec<u8, _> = Vec::with_capacity_in(0, &alloc);
+        assert!(empty.is_empty());
+        Ok(())
+    }
+
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+    /////
+    /////  Pinned threads
+    /////
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+
+    #[test]
+    fn spawn_on() -> Result<()> {
+        use std::sync::Arc;
+
+        let topo = Arc::new(Topology::builder()?.build()?);
+        if !topo.support().cpubind().get_thisthread_cpubind()
+            || !topo.support().cpubind().set_thisthread_cpubind()
+        {
+            eprintln!("** Thread binding is not supported; skipping");
+            return Ok(());
+        }
+
+        let initial = topo.cpubind(binding::CpuBindFlags::THREAD)?;
+        let pu = first_allowed_pu(&topo)?;
+        let t = Arc::clone(&topo);
+        let bound = topo
+            .spawn_on(pu, move || t.cpubind(binding::CpuBindFlags::THREAD))
+            .with_context(|| "failed to spawn a thread on the first allowed PU")?
+            .join()
+            .expect("pinned thread panicked")?;
+        assert_eq!(pu.cpuset(), Some(bound));
+        assert_eq!(initial, topo.cpubind(binding::CpuBindFlags::THREAD)?);
+        Ok(())
+    }
+
+    #[test]
+    fn pinned_builder_scoped() -> Result<()> {
+        use binding::{MemBindFlags, MemBindPolicy};
+        use thread::PinnedBuilder;
+
+        let topo = Topology::builder()?.build()?;
+        if !topo.support().cpubind().set_thisthread_cpubind()
+            || !topo.support().membind().set_thisthread_membind()
+            || !topo.support().membind().get_thisthread_membind()
+            || !topo.support().membind().bind_membind()
+        {
+            eprintln!("** Thread CPU or memory binding is not supported; skipping");
+            return Ok(());
+        }
+
+        let initial = topo.membind(MemBindFlags::THREAD)?;
+        let pu = first_allowed_pu(&topo)?;
+        let (cpuset, membind) = std::thread::scope(|s| -> Result<_> {
+            let handle = PinnedBuilder::new(&topo, pu)?
+                .name("pinned".to_string())
+                .singlify(true)
+                .membind(MemBindPolicy::Bind)
+                .spawn_scoped(s, || {
+                    assert_eq!(Some("pinned"), std::thread::current().name());
+                    (
+                        topo.cpubind(binding::CpuBindFlags::THREAD),
+                        topo.membind(MemBindFlags::THREAD),
+                    )
+                })?;
+            Ok(handle.join().expect("pinned thread panicked"))
+        })?;
+        let cpuset = cpuset?;
+        assert_eq!(1, cpuset.weight());
+        assert!(cpuset.is_included(&pu.cpuset().unwrap()));
+        assert_eq!((pu.nodeset().unwrap(), MemBindPolicy::Bind), membind?);
+        assert_eq!(initial, topo.membind(MemBindFlags::THREAD)?);
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn pinned_pool() -> Result<()> {
+        use std::sync::Arc;
+
+        use pool::PinnedPoolBuilder;
+
+        let topo = Arc::new(Topology::builder()?.build()?);
+        if !topo.support().cpubind().get_thisthread_cpubind()
+            || !topo.support().cpubind().set_thisthread_cpubind()
+        {
+            eprintln!("** Thread binding is not supported; skipping");
+            return Ok(());
+        }
+
+        let pool = PinnedPoolBuilder::new(Arc::clone(&topo), ObjectType::PU)
+            .thread_name(|idx| format!("pu-{}", idx))
+            .build()
+            .with_context(|| "failed to build a pool pinned per PU")?;
+        assert_eq!(
+            topo.nbobjs_by_type(ObjectType::PU) as usize,
+            pool.current_num_threads()
+        );
+        let bindings = pool.broadcast(|ctx| {
+            (
+                ctx.index(),
+                topo.cpubind(binding::CpuBindFlags::THREAD).unwrap(),
+            )
+        });
+        for (idx, cpuset) in bindings {
+            let pu = topo.object_by_type(ObjectType::PU, idx as u32).unwrap();
+            assert_eq!(pu.cpuset(), Some(cpuset));
+        }
+
+        assert!(matches!(
+            PinnedPoolBuilder::new(topo, ObjectType::Misc).build(),
+            Err(Error::ThreadPoolEmpty(ObjectType::Misc))
+        ));
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn pinned_pool_groups() -> Result<()> {
+        use std::sync::Arc;
+
+        use pool::PinnedPoolBuilder;
+
+        let topo = Arc::new(TopologyBuilder::from_synthetic("pack:1 group:2 pu:2")?.build()?);
+        assert_eq!(2, topo.nbobjs_by_type(ObjectType::Group));
+        // The synthetic backend provides empty binding hooks, so workers can always be bound.
+        let pool = PinnedPoolBuilder::new(Arc::clone(&topo), ObjectType::Group)
+            .build()
+            .with_context(|| "failed to build a pool pinned per Group")?;
+        assert_eq!(2, pool.current_num_threads());
+
+        let topo = TopologyBuilder::from_synthetic("pack:1 group:2 group:2 pu:2")?.build()?;
+        assert_eq!(-1, topo.nbobjs_by_type(ObjectType::Group));
+        assert!(matches!(
+            PinnedPoolBuilder::new(Arc::new(topo), ObjectType::Group).build(),
+            Err(Error::ThreadPoolMultipleDepths(ObjectType::Group))
+        ));
+        Ok(())
+    }
+
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+    /////
+    /////  Distances
+    /////
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+
+    #[test]
+    fn distances() -> Result<()> {
+        use distances::DistancesKind;
+
+        let topo = Topology::builder()?.build()?;
+        for dist in topo
+            .distances(DistancesKind::empty())
+            .with_context(|| "failed to retrieve distances")?
+        {
+            let n = dist.nbobjs();
+            assert_eq!(n * n, dist.values().len());
+            let objs: Vec<_> = dist.objects().collect();
+            assert_eq!(n, objs.len());
+            for (i, &a) in objs.iter().enumerate() {
+                assert_eq!(Some(i), dist.object_index(a));
+                for (j, &b) in objs.iter().enumerate() {
+                    assert_eq!(Some(dist.values()[i * n + j]), dist.value(a, b));
+                }
+            }
+        }
+
+        for dist in topo.distances_by_name("NUMALatency")? {
+            assert_eq!(Some("NUMALatency".to_string()), dist.name());
+            assert!(dist.kind().contains(DistancesKind::MEANS_LATENCY));
+            assert!(dist
+                .objects()
+                .all(|o| o.object_type() == ObjectType::NumaNode));
+        }
+        for dist in topo.distances_by_type(ObjectType::NumaNode, DistancesKind::MEANS_LATENCY)? {
+            let depth = topo.type_depth(ObjectType::NumaNode);
+            assert_eq!(
+                dist.nbobjs(),
+                topo.distances_by_depth(depth, DistancesKind::MEANS_LATENCY)?[0].nbobjs()
+            );
+        }
+        assert!(topo
+            .distances_by_name("NoSuchDistances")
+            .with_context(|| "failed to retrieve distances by name")?
+            .is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn add_and_remove_distances() -> Result<()> {
+        use distances::{DistancesAddFlags, DistancesKind};
+        use object::ObjectId;
+
+        let mut topo = TopologyBuilder::from_synthetic("pack:4 numa:1 core:1 pu:1")?.build()?;
+        let nodes: Vec<ObjectId> = (0..4)
+            .filter_map(|idx| topo.object_by_type(ObjectType::NumaNode, idx))
+            .map(ObjectId::from)
+            .collect();
+        assert_eq!(4, nodes.len());
+        let values: Vec<u64> = (0..16)
+            .map(|i| if i % 5 == 0 { 10 } else { 20 + i })
+            .collect();
+        let kind = DistancesKind::FROM_USER | DistancesKind::MEANS_LATENCY;
+
+        assert!(matches!(
+            topo.add_distances("TestLatency", kind)
+                .objects(&nodes)
+                .values(&values[1..])
+                .commit(DistancesAddFlags::empty()),
+            Err(Error::DistancesAdd(_))
+        ));
+        assert!(matches!(
+            topo.add_distances("TestLatency", DistancesKind::FROM_USER)
+                .objects(&nodes)
+                .values(&values)
+                .commit(DistancesAddFlags::empty()),
+            Err(Error::DistancesAdd(_))
+        ));
+        topo.add_distances("TestLatency", kind)
+            .objects(&nodes)
+            .values(&values)
+            .commit(DistancesAddFlags::empty())
+            .with_context(|| "failed to add distances")?;
+
+        let xml = {
+            let dists = topo.distances_by_name("TestLatency")?;
+            assert_eq!(1, dists.len());
+            assert_eq!(kind, dists[0].kind());
+            assert_eq!(values, dists[0].values());
+            let (a, b) = (
+                topo.object_by_id(nodes[1]).unwrap(),
+                topo.object_by_id(nodes[2]).unwrap(),
+            );
+            assert_eq!(Some(26), dists[0].value(a, b));
+            topo.to_xml_string(topology::XmlExportFlags::empty())?
+        };
+        let reloaded = TopologyBuilder::from_xml_str(&xml)?.build()?;
+        let dists = reloaded.distances_by_name("TestLatency")?;
+        assert_eq!(1, dists.len());
+        assert_eq!(values, dists[0].values());
+
+        topo.remove_distances_by_name("TestLatency")
+            .with_context(|| "failed to remove distances by name")?;
+        assert!(topo.distances_by_name("TestLatency")?.is_empty());
+
+        topo.add_distances("TestLatency", kind)
+            .objects(&nodes)
+            .values(&values)
+            .commit(DistancesAddFlags::empty())?;
+        topo.remove_distances_by_type(ObjectType::NumaNode)
+            .with_context(|| "failed to remove distances by type")?;
+        assert!(topo.distances(DistancesKind::FROM_USER)?.is_empty());
+        topo.remove_distances()
+            .with_context(|| "failed to remove all distances")?;
+        assert!(topo.distances(DistancesKind::empty())?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn transform_distances() -> Result<()> {
+        use distances::{DistancesAddFlags, DistancesKind, DistancesTransform};
+        use object::ObjectId;
+
+        let mut topo = TopologyBuilder::from_synthetic("pack:4 numa:1 core:1 pu:1")?.build()?;
+        let nodes: Vec<ObjectId> = (0..4)
+            .filter_map(|idx| topo.object_by_type(ObjectType::NumaNode, idx))
+            .map(ObjectId::from)
+            .collect();
+        let values: Vec<u64> = (0..16)
+            .map(|i| match (i / 4, i % 4) {
+                (from, to) if from == to => 100,
+                (0, 3) | (3, 0) => 0,
+                _ => 40,
+            })
+            .collect();
+        topo.add_distances(
+            "TestBandwidth",
+            DistancesKind::FROM_USER | DistancesKind::MEANS_BANDWIDTH,
+        )
+        .objects(&nodes)
+        .values(&values)
+        .commit(DistancesAddFlags::empty())?;
+
+        let mut dists = topo.distances_by_name("TestBandwidth")?;
+        assert_eq!(1, dists.len());
+        let links = &mut dists[0];
+        links
+            .transform(DistancesTransform::Links)
+            .with_context(|| "failed to transform bandwidths into links")?;
+        let expected: Vec<u64> = values.iter().map(|&v| (v == 40) as u64).collect();
+        assert_eq!(expected, links.values());
+
+        let mut dists = topo.distances_by_name("TestBandwidth")?;
+        let retained = &mut dists[0];
+        assert!(matches!(
+            retained.retain_objects(|obj| obj.logical_index() == 0),
+            Err(Error::DistancesTransform(DistancesTransform::RemoveNull))
+        ));
+        assert_eq!(4, retained.nbobjs());
+        assert_eq!(values, retained.values());
+        let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            retained.retain_objects(|obj| {
+                assert_ne!(3, obj.logical_index(), "retain_objects() closure panicked");
+                obj.logical_index() == 0
+            })
+        }));
+        assert!(panicked.is_err());
+        assert_eq!(4, retained.objects().count());
+        assert_eq!(values, retained.values());
+        retained
+            .retain_objects(|obj| obj.logical_index() != 1)
+            .with_context(|| "failed to remove objects from distances")?;
+        assert_eq!(3, retained.nbobjs());
+        assert_eq!(
+            vec![0, 2, 3],
+            retained
+                .objects()
+                .map(|obj| obj.logical_index())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!([100, 40, 0], retained.values()[..3]);
+
+        // The matrix that is stored in the topology is left unmodified.
+        assert_eq!(values, topo.distances_by_name("TestBandwidth")?[0].values());
+        Ok(())
+    }
+
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+    /////
+    /////  Memory attributes
+    /////
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+
+    #[test]
+    fn memattrs() -> Result<()> {
+        use memattr::{Initiator, MemAttrFlags, MemAttrId};
+
+        let topo = TopologyBuilder::from_synthetic("pack:2 numa:1 core:2 pu:1")?.build()?;
+        for (id, name, flags) in [
+            (MemAttrId::CAPACITY, "Capacity", MemAttrFlags::HIGHER_FIRST),
+            (
+                MemAttrId::BANDWIDTH,
+                "Bandwidth",
+                MemAttrFlags::HIGHER_FIRST | MemAttrFlags::NEED_INITIATOR,
+            ),
+            (
+                MemAttrId::LATENCY,
+                "Latency",
+                MemAttrFlags::LOWER_FIRST | MemAttrFlags::NEED_INITIATOR,
+            ),
+        ] {
+            let attr = topo
+                .memattr(id)
+                .with_context(|| format!("failed to retrieve memory attribute {:?}", id))?;
+            assert_eq!(Some(name.to_string()), attr.name());
+            assert_eq!(flags, attr.flags());
+            assert_eq!(id, topo.memattr_by_name(name)?.id());
+        }
+        assert!(matches!(
+            topo.memattr_by_name("Nonexistent"),
+            Err(Error::MemAttrGet)
+        ));
+
+        let locality = topo.memattr(MemAttrId::LOCALITY)?;
+        let targets = locality
+            .targets(None)
+            .with_context(|| "failed to retrieve locality targets")?;
+        assert_eq!(2, targets.len());
+        for (node, value) in targets {
+            assert_eq!(ObjectType::NumaNode, node.object_type());
+            assert_eq!(2, value);
+            assert_eq!(2, locality.value(node, None)?);
+            assert!(locality.initiators(node)?.is_empty());
+        }
+        assert!(locality.best_target(None)?.is_some());
+
+        // Synthetic topologies have no bandwidth values.
+        let initiator = Initiator::CpuSet(
+            topo.object_by_type(ObjectType::Core, 0)
+                .unwrap()
+                .cpuset()
+                .unwrap(),
+        );
+        let bandwidth = topo.memattr(MemAttrId::BANDWIDTH)?;
+        assert!(bandwidth.best_target(Some(&initiator))?.is_none());
+        assert!(bandwidth.targets(Some(&initiator))?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn register_and_set_memattrs() -> Result<()> {
+        use memattr::{Initiator, MemAttrFlags};
+        use object::ObjectId;
+
+        let mut topo = TopologyBuilder::from_synthetic("pack:2 numa:1 core:2 pu:1")?.build()?;
+        let flags = MemAttrFlags::HIGHER_FIRST | MemAttrFlags::NEED_INITIATOR;
+        let id = topo
+            .register_memattr("MeasuredReadBW", flags)
+            .with_context(|| "failed to register memory attribute")?;
+        assert!(matches!(
+            topo.register_memattr("MeasuredReadBW", flags),
+            Err(Error::MemAttrRegister(_))
+        ));
+        assert!(matches!(
+            topo.register_memattr("MeasuredWriteBW", MemAttrFlags::NEED_INITIATOR),
+            Err(Error::MemAttrRegister(_))
+        ));
+
+        let (nodes, packages): (Vec<ObjectId>, Vec<Initiator>) = (0..2)
+            .map(|idx| {
+                let node = topo.object_by_type(ObjectType::NumaNode, idx).unwrap();
+                let package = topo.object_by_type(ObjectType::Package, idx).unwrap();
+                (
+                    ObjectId::from(node),
+                    Initiator::CpuSet(package.cpuset().unwrap()),
+                )
+            })
+            .unzip();
+        for (i, &node) in nodes.iter().enumerate() {
+            for (j, package) in packages.iter().enumerate() {
+                let value = if i == j { 1000 * (i as u64 + 1) } else { 100 };
+                topo.set_memattr_value(id, node, Some(package), value)
+                    .with_context(|| "failed to set memory attribute value")?;
+            }
+        }
+        assert!(matches!(
+            topo.set_memattr_value(id, nodes[0], None, 0),
+            Err(Error::MemAttrSet)
+        ));
+
+        let xml = {
+            let attr = topo.memattr_by_name("MeasuredReadBW")?;
+            assert_eq!(id, attr.id());
+            assert_eq!(flags, attr.flags());
+            let node1 = topo.object_by_id(nodes[1]).unwrap();
+            assert_eq!(100, attr.value(node1, Some(&packages[0]))?);
+            let (best, value) = attr.best_target(Some(&packages[0]))?.unwrap();
+            assert_eq!((nodes[0], 1000), (ObjectId::from(best), value));
+            assert_eq!(2, attr.initiators(node1)?.len());
+            assert_eq!(
+                Some((packages[1].clone(), 2000)),
+                attr.best_initiator(node1)?
+            );
+            topo.to_xml_string(topology::XmlExportFlags::empty())?
+        };
+        let reloaded = TopologyBuilder::from_xml_str(&xml)?.build()?;
+        let attr = reloaded.memattr_by_name("MeasuredReadBW")?;
+        let node1 = reloaded.object_by_id(nodes[1]).unwrap();
+        assert_eq!(2000, attr.value(node1, Some(&packages[1]))?);
+        Ok(())
+    }
+
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+    /////
+    /////  Thread safety
+    /////
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+
+    fn assert_send<T: Send>() {}
+    fn assert_sync<T: Sync>() {}
+
+    #[test]
+    fn send_sync_types() {
+        assert_send::<Topology>();
+        assert_sync::<Topology>();
+        assert_send::<TopologyBuilder>();
+        assert_send::<Object<'_>>();
+        assert_sync::<Object<'_>>();
+        assert_send::<bitmap::Bitmap>();
+        assert_sync::<bitmap::Bitmap>();
+        assert_send::<topology::Support>();
+        assert_sync::<topology::Support>();
+    }
+
+    #[test]
+    fn shared_topology() -> Result<()> {
+        let topo = std::sync::Arc::new(
+            TopologyBuilder::from_synthetic("pack:2 numa:1 core:4 pu:2")?
+                .build()
+                .with_context(|| "failed to build the Topology")?,
+        );
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let topo = std::sync::Arc::clone(&topo);
+                std::thread::spawn(move || {
+                    let root = topo.root_object().expect("failed retrieving root object!");
+                    let pus: Vec<_> = (0..topo.nbobjs_by_type(ObjectType::PU) as u32)
+                        .filter_map(|idx| topo.object_by_type(ObjectType::PU, idx))
+                        .collect();
+                    assert_eq!(root.cpuset().map(|cpuset| cpuset.weight()), Some(16));
+                    pus.len()
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(16, handle.join().expect("worker thread panicked"));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn objects_across_scoped_threads() -> Result<()> {
+        let topo = TopologyBuilder::from_synthetic("pack:2 numa:1 core:4 pu:2")?.build()?;
+        let cores: Vec<_> = (0..topo.nbobjs_by_type(ObjectType::Core) as u32)
+            .filter_map(|idx| topo.object_by_type(ObjectType::Core, idx))
+            .collect();
+
+        // `Object`s borrow `topo`, so they may only be sent to threads that do not outlive it.
+        let weights: Vec<_> = std::thread::scope(|s| {
+            let handles: Vec<_> = cores
+                .iter()
+                .map(|&core| s.spawn(move || core.cpuset().map(|cpuset| cpuset.weight())))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("worker thread panicked"))
+                .collect()
+        });
+        assert_eq!(vec![Some(2); 8], weights);
+        Ok(())
+    }
+
     ///////////////////////////////////////////////////////////////////////////////////////////////
     /////
     /////  Print topology tree walk
@@ -542,34 +1993,32 @@ mod tests {
         Ok(())
     }
 
-    // FIXME(ckatsak): This triggers double-free, for some reason. Try:
-    //  $ valgrind \
-    //      --leak-check=full --track-origins=yes \
-    //      target/debug/deps/<TEST_BIN> 'tests::next_osdev'
-    // for more information, although that didn't really help me.
-    //#[test]
-    //fn next_osdev() -> Result<()> {
-    //    let topo = Topology::builder()
-    //        .with_context(|| "failed to create the TopologyBuilder")?
-    //        .io_types_filter(topology::Filter::KeepAll)?
-    //        .build()
-    //        .with_context(|| "failed to build the Topology")?;
-
-    //    let mut o = None;
-    //    while let Some(obj) = topo.next_osdev(o) {
-    //        eprintln!("==> OSDevice = {:#?}", obj);
-    //        eprintln!(
-    //            "\n{} ({}): #{}(L#{})\n└-attributes: {:?}\n\t└-non-io ancestor: {:?}\n",
-    //            obj,
-    //            obj.object_type(),
-    //            obj.os_index(),
-    //            obj.logical_index(),
-    //            obj.attributes(),
-    //            Topology::non_io_ancestor_object(obj),
-    //        );
-    //        let _prev = o.replace(obj);
-    //    }
+    #[test]
+    fn next_osdev() -> Result<()> {
+        let topo = Topology::builder()
+            .with_context(|| "failed to create the TopologyBuilder")?
+            .io_types_filter(topology::Filter::KeepAll)?
+            .build()
+            .with_context(|| "failed to build the Topology")?;
 
-    //    Ok(())
-    //}
+        let mut o = None;
+        while let Some(obj) = topo.next_osdev(o) {
+            eprintln!("==> OSDevice = {:#?}", obj);
+            eprintln!(
+                "\n{} ({}): #{}(L#{})\n└-attributes: {:?}\n\t└-non-io ancestor: {:?}\n",
+                obj,
+                obj.object_type(),
+                obj.os_index(),
+                obj.logical_index(),
+                obj.attributes(),
+                Topology::non_io_ancestor_object(obj),
+            );
+            // These used to take ownership of (and free) hwloc's strings, i.e., a double free.
+            assert_eq!(obj.name(), obj.name());
+            assert_eq!(obj.subtype(), obj.subtype());
+            let _prev = o.replace(obj);
+        }
+
+        Ok(())
+    }
 }
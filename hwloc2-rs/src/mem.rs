@@ -0,0 +1,561 @@
+//! Memory allocation on specific NUMA nodes.
+//!
+//! See also
+//! [Memory binding](https://www.open-mpi.org/projects/hwloc/doc/v2.7.1/a00167.php).
+
+use std::{
+    alloc::{GlobalAlloc, Layout},
+    fmt,
+    marker::PhantomData,
+    mem,
+    ops::{Deref, DerefMut},
+    ptr::{self, NonNull},
+    slice,
+    sync::{Mutex, PoisonError},
+};
+
+use crate::{
+    binding::{MemBindFlags, MemBindPolicy, MemBindSet},
+    bitmap::NodeSet,
+    error::Error,
+    ptr_mut_to_const,
+    topology::Topology,
+};
+
+/// An owned, fixed-size buffer of `T`s, whose memory is allocated through hwloc.
+///
+/// The memory is either allocated through `hwloc_alloc()`, following the default memory binding
+/// policy of the calling thread, or through `hwloc_alloc_membind()`, on the NUMA node(s) that are
+/// specified at creation. In both cases, it is always page-aligned, and it is freed through
+/// `hwloc_free()` when the buffer is dropped.
+///
+/// A `NumaBuffer` dereferences to a slice of its elements, and it may not outlive the
+/// [`Topology`] that it was allocated through:
+///
+/// ```no_run
+/// # use hwloc2::{binding::{MemBindFlags, MemBindPolicy, MemBindSet}, mem::NumaBuffer, Topology};
+/// let topo = Topology::builder()?.build()?;
+/// let nodeset = topo.topology_nodeset()?;
+/// let mut buf = NumaBuffer::from_elem_membind(
+///     &topo,
+///     1 << 20,
+///     MemBindSet::NodeSet(&nodeset),
+///     MemBindPolicy::Interleave,
+///     MemBindFlags::empty(),
+///     0u64,
+/// )?;
+/// buf[42] = 42;
+/// assert_eq!(42, buf.iter().sum::<u64>());
+/// # Ok::<(), hwloc2::Error>(())
+/// ```
+///
+/// [`Topology`]: crate::topology::Topology
+pub struct NumaBuffer<'topo, T> {
+    raw: RawBuffer<'topo>,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+// SAFETY: A `NumaBuffer` owns its elements, just like a `Box<[T]>`, and it only shares the
+// `Topology`, which is `Sync`.
+unsafe impl<T: Send> Send for NumaBuffer<'_, T> {}
+unsafe impl<T: Sync> Sync for NumaBuffer<'_, T> {}
+
+impl<'topo, T> NumaBuffer<'topo, T> {
+    /// Allocate a buffer of `len` elements, following the default memory binding policy of the
+    /// calling thread, and initialize each of them to `f(index)`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MemAlloc`] if hwloc fails to allocate the memory, or if `T` must be
+    /// aligned to more than a page.
+    ///
+    /// [`Error::MemAlloc`]: crate::error::Error::MemAlloc
+    pub fn from_fn<F>(topo: &'topo Topology, len: usize, f: F) -> Result<Self, Error>
+    where
+        F: FnMut(usize) -> T,
+    {
+        let raw = RawBuffer::alloc(topo, Self::layout(len)?, None)?;
+        Ok(Self::init(raw, len, f))
+    }
+
+    /// Allocate a buffer of `len` elements on the NUMA node(s) specified by `set`, and initialize
+    /// each of them to `f(index)`.
+    ///
+    /// Unless [`MemBindFlags::STRICT`] is given, hwloc may fall back to allocating the memory
+    /// without binding it, if the binding is not supported.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MemAlloc`] if hwloc fails to allocate or bind the memory, or if `T` must
+    /// be aligned to more than a page.
+    ///
+    /// [`Error::MemAlloc`]: crate::error::Error::MemAlloc
+    pub fn from_fn_membind<F>(
+        topo: &'topo Topology,
+        len: usize,
+        set: MemBindSet<'_>,
+        policy: MemBindPolicy,
+        flags: MemBindFlags,
+        f: F,
+    ) -> Result<Self, Error>
+    where
+        F: FnMut(usize) -> T,
+    {
+        let raw = RawBuffer::alloc(topo, Self::layout(len)?, Some((set, policy, flags)))?;
+        Ok(Self::init(raw, len, f))
+    }
+
+    /// The topology that the memory of the buffer was allocated through.
+    pub fn topology(&self) -> &'topo Topology {
+        self.raw.topo
+    }
+
+    /// The size of the memory of `len` elements, which must fit within a page-aligned allocation.
+    fn layout(len: usize) -> Result<usize, Error> {
+        if mem::align_of::<T>() > page_size() {
+            return Err(Error::MemAlloc);
+        }
+        mem::size_of::<T>()
+            .checked_mul(len)
+            .filter(|&size| size <= isize::MAX as usize)
+            .ok_or(Error::MemAlloc)
+    }
+
+    /// Initialize the `len` elements of `raw` to `f(index)`.
+    fn init<F>(raw: RawBuffer<'topo>, len: usize, mut f: F) -> Self
+    where
+        F: FnMut(usize) -> T,
+    {
+        // If `f` panics, the elements that have already been initialized are dropped by `guard`,
+        // while the memory itself is freed by `raw`.
+        let mut guard = InitGuard {
+            ptr: raw.ptr.as_ptr() as *mut T,
+            initialized: 0,
+        };
+        while guard.initialized < len {
+            // SAFETY: `raw` holds enough properly aligned memory for `len` elements.
+            unsafe { guard.ptr.add(guard.initialized).write(f(guard.initialized)) };
+            guard.initialized += 1;
+        }
+        mem::forget(guard);
+        Self {
+            raw,
+            len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'topo, T: Clone> NumaBuffer<'topo, T> {
+    /// Allocate a buffer of `len` clones of `elem`, following the default memory binding policy
+    /// of the calling thread.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MemAlloc`] if hwloc fails to allocate the memory, or if `T` must be
+    /// aligned to more than a page.
+    ///
+    /// [`Error::MemAlloc`]: crate::error::Error::MemAlloc
+    pub fn from_elem(topo: &'topo Topology, len: usize, elem: T) -> Result<Self, Error> {
+        Self::from_fn(topo, len, |_| elem.clone())
+    }
+
+    /// Allocate a buffer of `len` clones of `elem` on the NUMA node(s) specified by `set`.
+    ///
+    /// Unless [`MemBindFlags::STRICT`] is given, hwloc may fall back to allocating the memory
+    /// without binding it, if the binding is not supported.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MemAlloc`] if hwloc fails to allocate or bind the memory, or if `T` must
+    /// be aligned to more than a page.
+    ///
+    /// [`Error::MemAlloc`]: crate::error::Error::MemAlloc
+    pub fn from_elem_membind(
+        topo: &'topo Topology,
+        len: usize,
+        set: MemBindSet<'_>,
+        policy: MemBindPolicy,
+        flags: MemBindFlags,
+        elem: T,
+    ) -> Result<Self, Error> {
+        Self::from_fn_membind(topo, len, set, policy, flags, |_| elem.clone())
+    }
+}
+
+impl<T> Deref for NumaBuffer<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        // SAFETY: The memory holds `self.len` initialized, properly aligned elements, which are
+        // only accessed through `self`.
+        unsafe { slice::from_raw_parts(self.raw.ptr.as_ptr() as *const T, self.len) }
+    }
+}
+
+impl<T> DerefMut for NumaBuffer<'_, T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        // SAFETY: The memory holds `self.len` initialized, properly aligned elements, which are
+        // exclusively borrowed through `self`.
+        unsafe { slice::from_raw_parts_mut(self.raw.ptr.as_ptr() as *mut T, self.len) }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for NumaBuffer<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T> Drop for NumaBuffer<'_, T> {
+    fn drop(&mut self) {
+        let elems: *mut [T] = &mut **self;
+        // SAFETY: The elements are initialized, and they are never accessed again; the memory
+        // itself is freed right after, when `self.raw` is dropped.
+        unsafe { ptr::drop_in_place(elems) };
+    }
+}
+
+/// Smallest block that small allocations are served from; it fits a free list link.
+const MIN_BLOCK_SIZE: usize = 16;
+/// Largest block that small allocations are served from; larger ones get their own pages.
+const MAX_BLOCK_SIZE: usize = 2048;
+/// Number of (power-of-two) block sizes, from `MIN_BLOCK_SIZE` up to `MAX_BLOCK_SIZE`.
+const NUM_SIZE_CLASSES: usize = 8;
+/// Size of the chunks that are allocated through hwloc and carved into blocks of a single size.
+const CHUNK_SIZE: usize = 64 << 10;
+
+/// A memory allocator that places all of its allocations on the specified NUMA node(s).
+///
+/// Memory is allocated through `hwloc_alloc_membind()`, which can only allocate whole pages.
+/// Hence, to avoid wasting (at least) a page per allocation, small allocations (up to 2 KiB) are
+/// served from bound chunks that are carved into blocks of equal, power-of-two sizes, whereas
+/// larger ones get their own pages. Freed blocks are reused by subsequent allocations, while the
+/// chunks themselves are only returned to the OS when the allocator is dropped.
+///
+/// # Limitations
+///
+/// `NumaAllocator` implements [`GlobalAlloc`], but since it borrows its [`Topology`], it cannot
+/// be installed as the `#[global_allocator]`; on a stable toolchain, it can only be used by
+/// calling the unsafe [`GlobalAlloc::alloc`] and [`GlobalAlloc::dealloc`] methods directly (or,
+/// for owned buffers, see [`NumaBuffer`] instead).
+///
+/// With the `allocator_api` feature (which requires a nightly toolchain), it also implements the
+/// unstable `Allocator` trait, so that collections that accept an allocator, such as `Vec` and
+/// `Box`, may be placed on a given node through a `&NumaAllocator`. Collections without an
+/// allocator parameter, such as `std::collections::HashMap`, cannot be placed on a node this way.
+///
+/// ```ignore
+/// # use hwloc2::{mem::NumaAllocator, Topology};
+/// let topo = Topology::builder()?.build()?;
+/// let alloc = NumaAllocator::new(&topo, topo.topology_nodeset()?);
+/// let mut v = Vec::new_in(&alloc);
+/// v.extend(0..1024);
+/// # Ok::<(), hwloc2::Error>(())
+/// ```
+///
+/// [`GlobalAlloc`]: std::alloc::GlobalAlloc
+/// [`GlobalAlloc::alloc`]: std::alloc::GlobalAlloc::alloc
+/// [`GlobalAlloc::dealloc`]: std::alloc::GlobalAlloc::dealloc
+pub struct NumaAllocator<'topo> {
+    topo: &'topo Topology,
+    nodeset: NodeSet,
+    policy: MemBindPolicy,
+    flags: MemBindFlags,
+    arena: Mutex<Arena>,
+}
+
+impl<'topo> NumaAllocator<'topo> {
+    /// Create a new allocator that binds its memory to the NUMA node(s) of `nodeset`.
+    ///
+    /// The memory is bound with [`MemBindPolicy::Bind`], without any flags; i.e., hwloc may fall
+    /// back to allocating unbound memory, if the binding is not supported.
+    pub fn new(topo: &'topo Topology, nodeset: NodeSet) -> Self {
+        Self::with_policy(topo, nodeset, MemBindPolicy::Bind, MemBindFlags::empty())
+    }
+
+    /// Create a new allocator that binds its memory to the NUMA node(s) of `nodeset`, according
+    /// to the given `policy` and `flags`.
+    pub fn with_policy(
+        topo: &'topo Topology,
+        nodeset: NodeSet,
+        policy: MemBindPolicy,
+        flags: MemBindFlags,
+    ) -> Self {
+        Self {
+            topo,
+            nodeset,
+            policy,
+            flags,
+            arena: Mutex::new(Arena {
+                free: [ptr::null_mut(); NUM_SIZE_CLASSES],
+                chunks: ptr::null_mut(),
+            }),
+        }
+    }
+
+    /// The NUMA node(s) that the memory is bound to.
+    pub fn nodeset(&self) -> &NodeSet {
+        &self.nodeset
+    }
+
+    /// The memory binding policy of the allocations.
+    pub fn policy(&self) -> MemBindPolicy {
+        self.policy
+    }
+
+    /// The size class of the blocks that `layout` is served from, if it is small enough.
+    fn size_class(layout: Layout) -> Option<usize> {
+        if layout.size() > MAX_BLOCK_SIZE || layout.align() > MAX_BLOCK_SIZE {
+            return None;
+        }
+        let block_size = layout
+            .size()
+            .max(layout.align())
+            .max(MIN_BLOCK_SIZE)
+            .next_power_of_two();
+        Some((block_size.trailing_zeros() - MIN_BLOCK_SIZE.trailing_zeros()) as usize)
+    }
+
+    /// Allocate `size` bytes of bound, page-aligned memory through hwloc.
+    fn alloc_pages(&self, size: usize) -> *mut u8 {
+        // SAFETY: `self.topo` is a valid, loaded topology, and `self.nodeset` is a valid bitmap;
+        // hwloc does not modify either of them.
+        unsafe {
+            hwloc2_sys::hwloc_alloc_membind(
+                self.topo.as_ptr(),
+                size as _,
+                ptr_mut_to_const(self.nodeset.as_ptr()),
+                self.policy as i32,
+                self.flags.bits_by_nodeset(),
+            ) as *mut u8
+        }
+    }
+
+    /// Free `size` bytes of memory that were allocated by `alloc_pages()`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by `alloc_pages(size)` on `self`, and not freed since.
+    unsafe fn free_pages(&self, ptr: *mut u8, size: usize) {
+        hwloc2_sys::hwloc_free(self.topo.as_ptr(), ptr as *mut libc::c_void, size as _);
+    }
+
+    /// Pop a block of the given size class, refilling its free list with a new chunk if needed.
+    fn alloc_block(&self, class: usize) -> *mut u8 {
+        let mut arena = self.arena.lock().unwrap_or_else(PoisonError::into_inner);
+        if arena.free[class].is_null() {
+            let chunk = self.alloc_pages(CHUNK_SIZE);
+            if chunk.is_null() {
+                return ptr::null_mut();
+            }
+            // SAFETY: `chunk` is a new, page-aligned allocation of `CHUNK_SIZE` bytes.
+            unsafe { arena.add_chunk(chunk, class) };
+        }
+        let block = arena.free[class];
+        // SAFETY: `block` is a free block of the list, whose link was written when it was freed.
+        arena.free[class] = unsafe { (*block).next };
+        block as *mut u8
+    }
+
+    /// Push a block back to the free list of its size class.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by `alloc_block(class)` on `self`, and not freed since.
+    unsafe fn free_block(&self, ptr: *mut u8, class: usize) {
+        let mut arena = self.arena.lock().unwrap_or_else(PoisonError::into_inner);
+        let block = ptr as *mut FreeBlock;
+        block.write(FreeBlock {
+            next: arena.free[class],
+        });
+        arena.free[class] = block;
+    }
+}
+
+unsafe impl GlobalAlloc for NumaAllocator<'_> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        match Self::size_class(layout) {
+            Some(class) => self.alloc_block(class),
+            None if layout.align() <= page_size() => self.alloc_pages(layout.size()),
+            None => ptr::null_mut(),
+        }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        match Self::size_class(layout) {
+            Some(class) => self.free_block(ptr, class),
+            None => self.free_pages(ptr, layout.size()),
+        }
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+unsafe impl std::alloc::Allocator for NumaAllocator<'_> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, std::alloc::AllocError> {
+        let ptr = match layout.size() {
+            0 => layout.align() as *mut u8,
+            // SAFETY: `layout` has a non-zero size.
+            _ => unsafe { GlobalAlloc::alloc(self, layout) },
+        };
+        NonNull::new(ptr)
+            .map(|ptr| NonNull::slice_from_raw_parts(ptr, layout.size()))
+            .ok_or(std::alloc::AllocError)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        if layout.size() != 0 {
+            GlobalAlloc::dealloc(self, ptr.as_ptr(), layout);
+        }
+    }
+}
+
+impl fmt::Debug for NumaAllocator<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NumaAllocator")
+            .field("nodeset", &self.nodeset)
+            .field("policy", &self.policy)
+            .field("flags", &self.flags)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for NumaAllocator<'_> {
+    fn drop(&mut self) {
+        let arena = self.arena.get_mut().unwrap_or_else(PoisonError::into_inner);
+        let mut chunk = arena.chunks;
+        while !chunk.is_null() {
+            // SAFETY: `chunk` was allocated by `alloc_pages(CHUNK_SIZE)`, and it is no longer in
+            // use, since no allocation may outlive the allocator; its link is read before it is
+            // freed.
+            unsafe {
+                let next = (*chunk).next;
+                self.free_pages(chunk as *mut u8, CHUNK_SIZE);
+                chunk = next;
+            }
+        }
+    }
+}
+
+/// The free lists of the small blocks, along with the chunks that they have been carved from.
+///
+/// Both are linked through the (otherwise unused) memory itself, so that the allocator never
+/// allocates through the global allocator, which it may actually be.
+struct Arena {
+    free: [*mut FreeBlock; NUM_SIZE_CLASSES],
+    chunks: *mut Chunk,
+}
+
+// SAFETY: The arena exclusively owns the chunks that its lists point into, which are not tied to
+// any particular thread.
+unsafe impl Send for Arena {}
+
+impl Arena {
+    /// Carve `chunk` into blocks of the given size class, and push them to the respective free
+    /// list; the first block is reserved for linking the chunk itself.
+    ///
+    /// # Safety
+    ///
+    /// `chunk` must be a page-aligned allocation of `CHUNK_SIZE` bytes, which is not in use.
+    unsafe fn add_chunk(&mut self, chunk: *mut u8, class: usize) {
+        let header = chunk as *mut Chunk;
+        header.write(Chunk { next: self.chunks });
+        self.chunks = header;
+
+        let block_size = MIN_BLOCK_SIZE << class;
+        for offset in (block_size..CHUNK_SIZE).step_by(block_size).rev() {
+            let block = chunk.add(offset) as *mut FreeBlock;
+            block.write(FreeBlock {
+                next: self.free[class],
+            });
+            self.free[class] = block;
+        }
+    }
+}
+
+/// Link of a free block to the next one of the same size class.
+struct FreeBlock {
+    next: *mut FreeBlock,
+}
+
+/// Link of a chunk to the next one that has been allocated.
+struct Chunk {
+    next: *mut Chunk,
+}
+
+/// Drops the elements that have been initialized so far, in case initialization panics.
+struct InitGuard<T> {
+    ptr: *mut T,
+    initialized: usize,
+}
+
+impl<T> Drop for InitGuard<T> {
+    fn drop(&mut self) {
+        // SAFETY: The first `self.initialized` elements have been initialized.
+        unsafe { ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.ptr, self.initialized)) };
+    }
+}
+
+/// Uninitialized memory allocated through hwloc, which is freed through hwloc when dropped.
+struct RawBuffer<'topo> {
+    topo: &'topo Topology,
+    ptr: NonNull<libc::c_void>,
+    size: usize,
+}
+
+impl<'topo> RawBuffer<'topo> {
+    /// Allocate `size` bytes through `topo`, optionally bound as specified.
+    ///
+    /// Empty allocations do not reach hwloc; a dangling, page-aligned pointer is used instead.
+    fn alloc(
+        topo: &'topo Topology,
+        size: usize,
+        membind: Option<(MemBindSet<'_>, MemBindPolicy, MemBindFlags)>,
+    ) -> Result<Self, Error> {
+        if size == 0 {
+            return Ok(Self {
+                topo,
+                ptr: NonNull::new(page_size() as *mut libc::c_void).ok_or(Error::MemAlloc)?,
+                size,
+            });
+        }
+        let ptr = match membind {
+            // SAFETY: `topo` is a valid, loaded topology.
+            None => unsafe { hwloc2_sys::hwloc_alloc(topo.as_ptr(), size as _) },
+            // SAFETY: `topo` is a valid, loaded topology, and `set` refers to a valid bitmap;
+            // hwloc does not modify either of them.
+            Some((set, policy, flags)) => unsafe {
+                hwloc2_sys::hwloc_alloc_membind(
+                    topo.as_ptr(),
+                    size as _,
+                    ptr_mut_to_const(set.bitmap().as_ptr()),
+                    policy as i32,
+                    set.raw_flags(flags),
+                )
+            },
+        };
+        Ok(Self {
+            topo,
+            ptr: NonNull::new(ptr).ok_or(Error::MemAlloc)?,
+            size,
+        })
+    }
+}
+
+impl Drop for RawBuffer<'_> {
+    fn drop(&mut self) {
+        if self.size == 0 {
+            return;
+        }
+        // SAFETY: `self.ptr` was allocated by hwloc, with the given size, for the same topology,
+        // and it is freed only once, here.
+        unsafe { hwloc2_sys::hwloc_free(self.topo.as_ptr(), self.ptr.as_ptr(), self.size as _) };
+    }
+}
+
+/// The size of a memory page, or 0 if it cannot be determined.
+fn page_size() -> usize {
+    // SAFETY: `sysconf()` is always safe to call; it merely reports a configuration value.
+    usize::try_from(unsafe { libc::sysconf(libc::_SC_PAGESIZE) }).unwrap_or(0)
+}
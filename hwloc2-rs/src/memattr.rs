@@ -0,0 +1,441 @@
+//! [Memory attributes](https://www.open-mpi.org/projects/hwloc/doc/v2.7.1/a00188.php) of NUMA
+//! nodes, e.g., their capacity, or the bandwidth and latency of accesses to them from some
+//! initiator.
+//!
+//! Memory attributes are retrieved through [`Topology::memattr`] (or
+//! [`Topology::memattr_by_name`]), and may be used to select the best NUMA node(s) to allocate on,
+//! e.g., on machines with heterogeneous memory (HBM, NVDIMMs, etc.):
+//!
+//! ```no_run
+//! # use hwloc2::{
+//! #     binding::CpuBindFlags,
+//! #     mem::NumaAllocator,
+//! #     memattr::{Initiator, MemAttrId},
+//! #     Topology,
+//! # };
+//! let topo = Topology::builder()?.build()?;
+//! let initiator = Initiator::CpuSet(topo.cpubind(CpuBindFlags::THREAD)?);
+//! let bandwidth = topo.memattr(MemAttrId::BANDWIDTH)?;
+//! if let Some((node, _)) = bandwidth.best_target(Some(&initiator))? {
+//!     let alloc = NumaAllocator::new(&topo, node.nodeset().unwrap());
+//! }
+//! # Ok::<(), hwloc2::Error>(())
+//! ```
+//!
+//! Applications may also register their own attributes (e.g., measured bandwidths) through
+//! [`Topology::register_memattr`], and set their values through [`Topology::set_memattr_value`].
+//!
+//! [`Topology::memattr`]: crate::topology::Topology::memattr
+//! [`Topology::memattr_by_name`]: crate::topology::Topology::memattr_by_name
+//! [`Topology::register_memattr`]: crate::topology::Topology::register_memattr
+//! [`Topology::set_memattr_value`]: crate::topology::Topology::set_memattr_value
+
+use std::{ffi::CStr, fmt, os::raw::c_uint, ptr};
+
+use crate::{
+    bitmap::CpuSet,
+    error::Error,
+    object::{Object, ObjectId},
+    topology::Topology,
+};
+
+/// Identifier of a memory attribute.
+///
+/// It is either one of the attributes that are predefined by hwloc (i.e., the associated
+/// constants), or one that was registered by the application.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MemAttrId(pub(crate) u32);
+
+impl MemAttrId {
+    /// The capacity of NUMA nodes, in bytes (i.e., their local memory).
+    ///
+    /// Best nodes are nodes with a higher capacity. No initiator is involved.
+    pub const CAPACITY: Self = Self(hwloc2_sys::hwloc_memattr_id_e_HWLOC_MEMATTR_ID_CAPACITY);
+
+    /// The locality of NUMA nodes, as the number of PUs in that locality (i.e., the weight of
+    /// their CPU set).
+    ///
+    /// Best nodes are nodes with a smaller locality, i.e., that are local to very few PUs. No
+    /// initiator is involved.
+    pub const LOCALITY: Self = Self(hwloc2_sys::hwloc_memattr_id_e_HWLOC_MEMATTR_ID_LOCALITY);
+
+    /// The bandwidth of accesses to NUMA nodes from a given initiator, in MiB/s.
+    ///
+    /// Best nodes are nodes with a higher bandwidth.
+    pub const BANDWIDTH: Self = Self(hwloc2_sys::hwloc_memattr_id_e_HWLOC_MEMATTR_ID_BANDWIDTH);
+
+    /// The latency of accesses to NUMA nodes from a given initiator, in nanoseconds.
+    ///
+    /// Best nodes are nodes with a smaller latency.
+    pub const LATENCY: Self = Self(hwloc2_sys::hwloc_memattr_id_e_HWLOC_MEMATTR_ID_LATENCY);
+}
+
+bitflags::bitflags! {
+    /// Flags of memory attributes.
+    #[derive(Default)]
+    #[repr(C)]
+    pub struct MemAttrFlags: u64 {
+        /// The best nodes for this memory attribute are those with the higher values (e.g.,
+        /// bandwidth).
+        const HIGHER_FIRST =
+            hwloc2_sys::hwloc_memattr_flag_e_HWLOC_MEMATTR_FLAG_HIGHER_FIRST as u64;
+
+        /// The best nodes for this memory attribute are those with the lower values (e.g.,
+        /// latency).
+        const LOWER_FIRST = hwloc2_sys::hwloc_memattr_flag_e_HWLOC_MEMATTR_FLAG_LOWER_FIRST as u64;
+
+        /// The values of this memory attribute depend on the given initiator (e.g., bandwidth and
+        /// latency, but not capacity).
+        const NEED_INITIATOR =
+            hwloc2_sys::hwloc_memattr_flag_e_HWLOC_MEMATTR_FLAG_NEED_INITIATOR as u64;
+    }
+}
+
+/// Where the memory accesses that a memory attribute value relates to are performed from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Initiator {
+    /// Accesses performed by the CPU cores of a CPU set, e.g., the current binding of a thread.
+    ///
+    /// This is the kind of initiator that hwloc uses internally.
+    CpuSet(CpuSet),
+
+    /// Accesses performed by an object, e.g., by a GPU.
+    ///
+    /// The object is identified by its [`ObjectId`], so that an `Initiator` may also be given to
+    /// methods that modify the topology, e.g., [`Topology::set_memattr_value`].
+    ///
+    /// [`Topology::set_memattr_value`]: crate::topology::Topology::set_memattr_value
+    Object(ObjectId),
+}
+
+impl Initiator {
+    /// The raw hwloc location of this initiator in `topo`, which borrows from `self`, or `None` if
+    /// its object is not part of `topo`.
+    pub(crate) fn to_raw(&self, topo: &Topology) -> Option<hwloc2_sys::hwloc_location> {
+        Some(match self {
+            Initiator::CpuSet(cpuset) => hwloc2_sys::hwloc_location {
+                type_: hwloc2_sys::hwloc_location_type_e_HWLOC_LOCATION_TYPE_CPUSET,
+                location: hwloc2_sys::hwloc_location_hwloc_location_u {
+                    cpuset: cpuset.as_ptr(),
+                },
+            },
+            Initiator::Object(id) => hwloc2_sys::hwloc_location {
+                type_: hwloc2_sys::hwloc_location_type_e_HWLOC_LOCATION_TYPE_OBJECT,
+                location: hwloc2_sys::hwloc_location_hwloc_location_u {
+                    object: topo.object_by_id(*id)?.as_ptr() as *mut _,
+                },
+            },
+        })
+    }
+
+    /// Copy the raw hwloc location `raw` into a new `Initiator`.
+    ///
+    /// # Safety
+    ///
+    /// The caller must make sure that `raw` is a valid location returned by hwloc.
+    unsafe fn from_raw(raw: &hwloc2_sys::hwloc_location) -> Result<Self, Error> {
+        match raw.type_ {
+            hwloc2_sys::hwloc_location_type_e_HWLOC_LOCATION_TYPE_CPUSET => {
+                CpuSet::copy_from_raw(raw.location.cpuset).map(Initiator::CpuSet)
+            }
+            _ => Ok(Initiator::Object(ObjectId::from(Object::new(
+                raw.location.object,
+            )))),
+        }
+    }
+}
+
+/// Pointer to an optional raw hwloc location, or `NULL`.
+pub(crate) fn location_ptr(
+    location: &mut Option<hwloc2_sys::hwloc_location>,
+) -> *mut hwloc2_sys::hwloc_location {
+    location
+        .as_mut()
+        .map_or(ptr::null_mut(), |location| location as *mut _)
+}
+
+/// A memory attribute of the NUMA nodes of a [`Topology`].
+///
+/// Its values relate to target NUMA nodes and, if it has the [`MemAttrFlags::NEED_INITIATOR`]
+/// flag, to the initiator of the memory accesses. Queries that take an optional initiator ignore
+/// it for attributes that do not need one.
+#[derive(Clone, Copy)]
+pub struct MemAttr<'topo> {
+    topo: &'topo Topology,
+    id: MemAttrId,
+    flags: MemAttrFlags,
+}
+
+impl<'topo> MemAttr<'topo> {
+    /// Retrieve the memory attribute `id` of `topo`.
+    pub(crate) fn new(topo: &'topo Topology, id: MemAttrId) -> Result<Self, Error> {
+        let mut flags = 0;
+        // SAFETY: `topo` is a valid, loaded topology, and `flags` is a valid pointer.
+        match unsafe { hwloc2_sys::hwloc_memattr_get_flags(topo.as_ptr(), id.0, &mut flags) } {
+            -1 => Err(Error::MemAttrGet),
+            _ => Ok(Self {
+                topo,
+                id,
+                flags: MemAttrFlags::from_bits_truncate(flags),
+            }),
+        }
+    }
+
+    /// The identifier of the attribute.
+    pub fn id(&self) -> MemAttrId {
+        self.id
+    }
+
+    /// The name of the attribute (e.g., `"Bandwidth"`).
+    pub fn name(&self) -> Option<String> {
+        let mut name = ptr::null();
+        // SAFETY: `self.topo` is a valid, loaded topology, `self.id` is a valid attribute of it,
+        // and `name` is a valid pointer.
+        if unsafe { hwloc2_sys::hwloc_memattr_get_name(self.topo.as_ptr(), self.id.0, &mut name) }
+            == -1
+            || name.is_null()
+        {
+            return None;
+        }
+        // SAFETY: Since `name` != NULL, it should be a valid C string according to hwloc, which
+        // remains owned by the topology (i.e., it must not be freed here).
+        let cstr = unsafe { CStr::from_ptr(name) };
+        cstr.to_str().ok().map(|s| s.to_owned())
+    }
+
+    /// The flags of the attribute.
+    pub fn flags(&self) -> MemAttrFlags {
+        self.flags
+    }
+
+    /// The value of the attribute for the NUMA node `target`, as seen from `initiator`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MemAttrQuery`] in case of failure reported by hwloc, e.g., if `target`
+    /// has no value, or if the attribute needs an initiator and none is given.
+    ///
+    /// [`Error::MemAttrQuery`]: crate::error::Error::MemAttrQuery
+    pub fn value(&self, target: Object<'_>, initiator: Option<&Initiator>) -> Result<u64, Error> {
+        let mut location = initiator
+            .map(|initiator| initiator.to_raw(self.topo).ok_or(Error::MemAttrQuery))
+            .transpose()?;
+        let mut value = 0;
+        // SAFETY: `self.topo` is a valid, loaded topology, `self.id` is a valid attribute of it,
+        // `target` is one of its objects, `location` is either NULL or a valid location in it
+        // (see `Initiator::to_raw`), and `value` is a valid pointer.
+        match unsafe {
+            hwloc2_sys::hwloc_memattr_get_value(
+                self.topo.as_ptr(),
+                self.id.0,
+                target.as_ptr() as *mut _,
+                location_ptr(&mut location),
+                0,
+                &mut value,
+            )
+        } {
+            -1 => Err(Error::MemAttrQuery),
+            _ => Ok(value),
+        }
+    }
+
+    /// The best NUMA node for the attribute, as seen from `initiator`, along with its value, or
+    /// `None` if no node has a value.
+    ///
+    /// If multiple nodes have the same value, only one of them is returned; [`MemAttr::targets`]
+    /// may be used to look at all of them instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MemAttrQuery`] in case of failure reported by hwloc, e.g., if the
+    /// attribute needs an initiator and none is given.
+    ///
+    /// [`Error::MemAttrQuery`]: crate::error::Error::MemAttrQuery
+    pub fn best_target(
+        &self,
+        initiator: Option<&Initiator>,
+    ) -> Result<Option<(Object<'topo>, u64)>, Error> {
+        let mut location = initiator
+            .map(|initiator| initiator.to_raw(self.topo).ok_or(Error::MemAttrQuery))
+            .transpose()?;
+        let (mut target, mut value) = (ptr::null_mut(), 0);
+        // SAFETY: `self.topo` is a valid, loaded topology, `self.id` is a valid attribute of it,
+        // `location` is either NULL or a valid location in it (see `Initiator::to_raw`), and
+        // `target` and `value` are valid pointers.
+        match unsafe {
+            hwloc2_sys::hwloc_memattr_get_best_target(
+                self.topo.as_ptr(),
+                self.id.0,
+                location_ptr(&mut location),
+                0,
+                &mut target,
+                &mut value,
+            )
+        } {
+            -1 if std::io::Error::last_os_error().raw_os_error() == Some(libc::ENOENT) => Ok(None),
+            -1 => Err(Error::MemAttrQuery),
+            // SAFETY: On success, `target` is a valid NUMA node of `self.topo`.
+            _ => Ok(Some((unsafe { Object::new(target) }, value))),
+        }
+    }
+
+    /// The best initiator for the attribute and the NUMA node `target`, along with its value, or
+    /// `None` if no initiator has a value.
+    ///
+    /// If multiple initiators have the same value, only one of them is returned;
+    /// [`MemAttr::initiators`] may be used to look at all of them instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MemAttrQuery`] in case of failure reported by hwloc, e.g., if the
+    /// attribute does not have the [`MemAttrFlags::NEED_INITIATOR`] flag.
+    ///
+    /// [`Error::MemAttrQuery`]: crate::error::Error::MemAttrQuery
+    pub fn best_initiator(&self, target: Object<'_>) -> Result<Option<(Initiator, u64)>, Error> {
+        // SAFETY: An all-zero `hwloc_location` is a valid value, i.e., an object location with a
+        // NULL object, which is only ever read once it is overwritten by hwloc.
+        let mut location: hwloc2_sys::hwloc_location = unsafe { std::mem::zeroed() };
+        let mut value = 0;
+        // SAFETY: `self.topo` is a valid, loaded topology, `self.id` is a valid attribute of it,
+        // `target` is one of its objects, and `location` and `value` are valid pointers.
+        match unsafe {
+            hwloc2_sys::hwloc_memattr_get_best_initiator(
+                self.topo.as_ptr(),
+                self.id.0,
+                target.as_ptr() as *mut _,
+                0,
+                &mut location,
+                &mut value,
+            )
+        } {
+            -1 if std::io::Error::last_os_error().raw_os_error() == Some(libc::ENOENT) => Ok(None),
+            -1 => Err(Error::MemAttrQuery),
+            // SAFETY: On success, `location` is a valid location of `self.topo`.
+            _ => Ok(Some((unsafe { Initiator::from_raw(&location) }?, value))),
+        }
+    }
+
+    /// The NUMA nodes that have a value for the attribute (as seen from `initiator`, if any),
+    /// along with their values.
+    ///
+    /// This is meant for listing all values; [`MemAttr::best_target`], or comparing the values of
+    /// a few local NUMA nodes through [`MemAttr::value`], is usually preferable for selecting
+    /// where to allocate.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MemAttrQuery`] in case of failure reported by hwloc.
+    ///
+    /// [`Error::MemAttrQuery`]: crate::error::Error::MemAttrQuery
+    pub fn targets(
+        &self,
+        initiator: Option<&Initiator>,
+    ) -> Result<Vec<(Object<'topo>, u64)>, Error> {
+        let mut location = initiator
+            .map(|initiator| initiator.to_raw(self.topo).ok_or(Error::MemAttrQuery))
+            .transpose()?;
+        let location = location_ptr(&mut location);
+        let (targets, values) = collect(|nr, targets, values| {
+            // SAFETY: `self.topo` is a valid, loaded topology, `self.id` is a valid attribute of
+            // it, `location` is either NULL or a valid location in it (see `Initiator::to_raw`),
+            // and `collect` provides valid `nr`, `targets` and `values` arguments.
+            unsafe {
+                hwloc2_sys::hwloc_memattr_get_targets(
+                    self.topo.as_ptr(),
+                    self.id.0,
+                    location,
+                    0,
+                    nr,
+                    targets,
+                    values,
+                )
+            }
+        })?;
+        Ok(targets
+            .into_iter()
+            // SAFETY: The returned targets are valid NUMA nodes of `self.topo`.
+            .map(|target| unsafe { Object::new(target) })
+            .zip(values)
+            .collect())
+    }
+
+    /// The initiators that have a value for the attribute and the NUMA node `target`, along with
+    /// their values.
+    ///
+    /// There are none if the attribute does not have the [`MemAttrFlags::NEED_INITIATOR`] flag.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MemAttrQuery`] in case of failure reported by hwloc.
+    ///
+    /// [`Error::MemAttrQuery`]: crate::error::Error::MemAttrQuery
+    pub fn initiators(&self, target: Object<'_>) -> Result<Vec<(Initiator, u64)>, Error> {
+        let (initiators, values) = collect(|nr, initiators, values| {
+            // SAFETY: `self.topo` is a valid, loaded topology, `self.id` is a valid attribute of
+            // it, `target` is one of its objects, and `collect` provides valid `nr`, `initiators`
+            // and `values` arguments.
+            unsafe {
+                hwloc2_sys::hwloc_memattr_get_initiators(
+                    self.topo.as_ptr(),
+                    self.id.0,
+                    target.as_ptr() as *mut _,
+                    0,
+                    nr,
+                    initiators,
+                    values,
+                )
+            }
+        })?;
+        initiators
+            .iter()
+            // SAFETY: The returned initiators are valid locations of `self.topo`.
+            .map(|location| unsafe { Initiator::from_raw(location) })
+            .zip(values)
+            .map(|(initiator, value)| Ok((initiator?, value)))
+            .collect()
+    }
+}
+
+impl fmt::Debug for MemAttr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemAttr")
+            .field("id", &self.id)
+            .field("name", &self.name())
+            .field("flags", &self.flags)
+            .finish()
+    }
+}
+
+/// Retrieve an array of items and their values through `get`, which is called twice: first to
+/// get the number of items, and then to actually retrieve them.
+fn collect<T>(
+    get: impl Fn(*mut c_uint, *mut T, *mut u64) -> i32,
+) -> Result<(Vec<T>, Vec<u64>), Error> {
+    let mut nr = 0;
+    if get(&mut nr, ptr::null_mut(), ptr::null_mut()) == -1 {
+        return Err(Error::MemAttrQuery);
+    }
+    let capacity = nr;
+    let mut items = Vec::with_capacity(capacity as usize);
+    let mut values = vec![0; capacity as usize];
+    if get(&mut nr, items.as_mut_ptr(), values.as_mut_ptr()) == -1 {
+        return Err(Error::MemAttrQuery);
+    }
+    let len = nr.min(capacity) as usize;
+    // SAFETY: hwloc stored (at least) `len` items in the first `capacity` slots of `items`.
+    unsafe { items.set_len(len) };
+    values.truncate(len);
+    Ok((items, values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MemAttrFlags, MemAttrId};
+
+    #[test]
+    fn memattr_flags_and_ids() {
+        assert!(MemAttrFlags::default().is_empty());
+        assert_eq!(0b111, MemAttrFlags::all().bits());
+        assert_eq!(MemAttrId(3), MemAttrId::LATENCY);
+    }
+}
@@ -11,6 +11,12 @@ pub struct NumaNodeAttributes<'topo> {
     _marker: PhantomData<&'topo hwloc2_sys::hwloc_obj_attr_u>,
 }
 
+// SAFETY: `ptr` points to the attributes of a NUMA node, which (like its `page_types` array)
+// are owned by the `Topology` borrowed for `'topo`; hwloc only modifies them through `&mut
+// Topology` methods, so they are only read for as long as this borrow lasts.
+unsafe impl Send for NumaNodeAttributes<'_> {}
+unsafe impl Sync for NumaNodeAttributes<'_> {}
+
 impl<'topo> NumaNodeAttributes<'topo> {
     /// Create a new NumaNodeAttributes.
     ///
@@ -104,6 +110,12 @@ pub struct CacheAttributes<'topo> {
     _marker: PhantomData<&'topo hwloc2_sys::hwloc_obj_attr_u>,
 }
 
+// SAFETY: `ptr` points to the attributes of a cache object of the `Topology` borrowed for
+// `'topo`; they are set when the cache is discovered and only read afterwards, while the object
+// itself may only be removed through `&mut Topology` methods, which the borrow rules out.
+unsafe impl Send for CacheAttributes<'_> {}
+unsafe impl Sync for CacheAttributes<'_> {}
+
 impl<'topo> CacheAttributes<'topo> {
     /// Create a new CacheAttributes.
     ///
@@ -195,6 +207,12 @@ pub struct PciDevAttributes<'topo> {
     _marker: PhantomData<&'topo hwloc2_sys::hwloc_obj_attr_u_hwloc_pcidev_attr_s>,
 }
 
+// SAFETY: `ptr` points to the PCI attributes of a PCI device, or to the upstream attributes of a
+// bridge, of the `Topology` borrowed for `'topo`; hwloc only fills them during I/O discovery, so
+// they are only read for as long as this borrow lasts.
+unsafe impl Send for PciDevAttributes<'_> {}
+unsafe impl Sync for PciDevAttributes<'_> {}
+
 impl<'topo> PciDevAttributes<'topo> {
     /// Create a new PciDevAttributes.
     ///
@@ -318,6 +336,12 @@ pub struct BridgeAttributes<'topo> {
     _marker: PhantomData<&'topo hwloc2_sys::hwloc_obj_attr_u>,
 }
 
+// SAFETY: `ptr` points to the attributes of a bridge of the `Topology` borrowed for `'topo`,
+// which hwloc only fills during I/O discovery; the `PciDevAttributes` that it hands out for the
+// upstream side borrow it, and thus cannot outlive that `Topology` borrow either.
+unsafe impl Send for BridgeAttributes<'_> {}
+unsafe impl Sync for BridgeAttributes<'_> {}
+
 impl<'topo> BridgeAttributes<'topo> {
     /// Create a new BridgeAttributes.
     ///
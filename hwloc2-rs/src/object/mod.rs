@@ -1,11 +1,6 @@
 pub mod attributes;
 
-use std::{
-    ffi::{CStr, CString},
-    fmt,
-    marker::PhantomData,
-    ptr::addr_of,
-};
+use std::{ffi::CStr, fmt, marker::PhantomData, ptr::addr_of};
 
 use num_traits::FromPrimitive;
 
@@ -15,12 +10,47 @@ use crate::{
 };
 use attributes::{BridgeAttributes, CacheAttributes, NumaNodeAttributes, PciDevAttributes};
 
+/// A read-only handle to an object of a [`Topology`].
+///
+/// An `Object` borrows the [`Topology`] it belongs to, so it may be shared with (or sent to)
+/// other threads for as long as the [`Topology`] is borrowed, e.g. via [`std::thread::scope`]:
+///
+/// ```no_run
+/// # use hwloc2::{ObjectType, Topology};
+/// let topo = Topology::builder()?.build()?;
+/// let pus: Vec<_> = (0..topo.nbobjs_by_type(ObjectType::PU) as u32)
+///     .filter_map(|idx| topo.object_by_type(ObjectType::PU, idx))
+///     .collect();
+/// std::thread::scope(|s| {
+///     for pu in &pus {
+///         s.spawn(move || println!("{}: {:?}", pu, pu.cpuset()));
+///     }
+/// });
+/// # Ok::<(), hwloc2::Error>(())
+/// ```
+///
+/// However, it may not outlive the [`Topology`]:
+///
+/// ```compile_fail
+/// # use hwloc2::Topology;
+/// let topo = Topology::builder()?.build()?;
+/// let root = topo.root_object().unwrap();
+/// std::thread::spawn(move || println!("{}", root)); // `topo` does not live long enough
+/// # Ok::<(), hwloc2::Error>(())
+/// ```
+///
+/// [`Topology`]: crate::topology::Topology
 #[derive(Clone, Copy)]
 pub struct Object<'topo> {
     ptr: *const hwloc2_sys::hwloc_obj,
     _marker: PhantomData<&'topo hwloc2_sys::hwloc_obj>,
 }
 
+// SAFETY: An `Object` is a shared borrow of (part of) a loaded `Topology`, through which hwloc's
+// data are only ever read, and `Topology` is `Sync`.
+unsafe impl Send for Object<'_> {}
+unsafe impl Sync for Object<'_> {}
+
 impl<'topo> Object<'topo> {
     /// The value returned by [`Object::os_index`] when it is unknown or irrelevant for the object.
     ///
@@ -42,6 +72,11 @@ impl<'topo> Object<'topo> {
         }
     }
 
+    /// The underlying hwloc object.
+    pub(crate) fn as_ptr(&self) -> *const hwloc2_sys::hwloc_obj {
+        self.ptr
+    }
+
     /// Type of object.
     pub fn object_type(&self) -> ObjectType {
         // SAFETY: `self.ptr` can be safely dereferenced because it was created either via `new()`
@@ -67,9 +102,10 @@ impl<'topo> Object<'topo> {
         if o.subtype.is_null() {
             return None;
         }
-        // SAFETY: Since `o.subtype` != NULL, it should be a valid C string according to hwloc
-        let cstring = unsafe { CString::from_raw(o.subtype) };
-        cstring.to_str().ok().map(|s| s.to_owned())
+        // SAFETY: Since `o.subtype` != NULL, it should be a valid C string according to hwloc,
+        // which remains owned by the topology (i.e., it must not be freed here).
+        let cstr = unsafe { CStr::from_ptr(o.subtype) };
+        cstr.to_str().ok().map(|s| s.to_owned())
     }
 
     /// OS-provided physical index number. It is not guaranteed unique across the entire machine,
@@ -90,9 +126,10 @@ impl<'topo> Object<'topo> {
         if o.name.is_null() {
             return None;
         }
-        // SAFETY: Since `o.name` != NULL, it should be a valid C string according to hwloc
-        let cstring = unsafe { CString::from_raw(o.name) };
-        cstring.to_str().ok().map(|s| s.to_owned())
+        // SAFETY: Since `o.name` != NULL, it should be a valid C string according to hwloc, which
+        // remains owned by the topology (i.e., it must not be freed here).
+        let cstr = unsafe { CStr::from_ptr(o.name) };
+        cstr.to_str().ok().map(|s| s.to_owned())
     }
 
     /// Total memory (in bytes) in NUMA nodes below this object.
@@ -277,14 +314,13 @@ impl<'topo> Object<'topo> {
     /// # Notes
     ///
     /// - All objects have non-NULL CPU and node sets except Misc and I/O objects.
-    /// - Its value must not be changed, [`Bitmap::clone`] must be used instead.
+    /// - The returned bitmap is a newly allocated copy; changing it does not affect the topology.
     ///
     /// [`Flags::INCLUDE_DISALLOWED`]: crate::topology::flags::Flags::INCLUDE_DISALLOWED
-    /// [`Bitmap::clone`]: crate::bitmap::Bitmap::clone
     pub fn cpuset(&self) -> Option<CpuSet> {
         // SAFETY: `self.ptr` can be safely dereferenced because it was created either via `new()`
         // or based on another (valid) `Object`'s (valid) pointer, and remained private ever since.
-        unsafe { Bitmap::from_raw((*self.ptr).cpuset, false) }.ok()
+        unsafe { Bitmap::copy_from_raw((*self.ptr).cpuset) }.ok()
     }
 
     /// TODO: UNTESTED
@@ -299,14 +335,13 @@ impl<'topo> Object<'topo> {
     ///
     /// # Note
     ///
-    /// Its value must not be changed, [`Bitmap::clone`] must be used instead.
+    /// The returned bitmap is a newly allocated copy; changing it does not affect the topology.
     ///
     /// [`Flags::INCLUDE_DISALLOWED`]: crate::topology::flags::Flags::INCLUDE_DISALLOWED
-    /// [`Bitmap::clone`]: crate::bitmap::Bitmap::clone
     pub fn complete_cpuset(&self) -> Option<CpuSet> {
         // SAFETY: `self.ptr` can be safely dereferenced because it was created either via `new()`
         // or based on another (valid) `Object`'s (valid) pointer, and remained private ever since.
-        unsafe { Bitmap::from_raw((*self.ptr).complete_cpuset, false) }.ok()
+        unsafe { Bitmap::copy_from_raw((*self.ptr).complete_cpuset) }.ok()
     }
 
     /// TODO: UNTESTED
@@ -332,14 +367,13 @@ impl<'topo> Object<'topo> {
     /// # Note
     ///
     /// - All objects have non-NULL CPU and node sets except Misc and I/O objects.
-    /// - Its value must not be changed, [`Bitmap::clone`] must be used instead.
+    /// - The returned bitmap is a newly allocated copy; changing it does not affect the topology.
     ///
     /// [`Flags::INCLUDE_DISALLOWED`]: crate::topology::flags::Flags::INCLUDE_DISALLOWED
-    /// [`Bitmap::clone`]: crate::bitmap::Bitmap::clone
     pub fn nodeset(&self) -> Option<NodeSet> {
         // SAFETY: `self.ptr` can be safely dereferenced because it was created either via `new()`
         // or based on another (valid) `Object`'s (valid) pointer, and remained private ever since.
-        unsafe { Bitmap::from_raw((*self.ptr).nodeset, false) }.ok()
+        unsafe { Bitmap::copy_from_raw((*self.ptr).nodeset) }.ok()
     }
 
     /// TODO: UNTESTED
@@ -357,14 +391,13 @@ impl<'topo> Object<'topo> {
     ///
     /// # Note
     ///
-    /// - Its value must not be changed, [`Bitmap::clone`] must be used instead.
+    /// - The returned bitmap is a newly allocated copy; changing it does not affect the topology.
     ///
     /// [`Flags::INCLUDE_DISALLOWED`]: crate::topology::flags::Flags::INCLUDE_DISALLOWED
-    /// [`Bitmap::clone`]: crate::bitmap::Bitmap::clone
     pub fn complete_nodeset(&self) -> Option<NodeSet> {
         // SAFETY: `self.ptr` can be safely dereferenced because it was created either via `new()`
         // or based on another (valid) `Object`'s (valid) pointer, and remained private ever since.
-        unsafe { Bitmap::from_raw((*self.ptr).complete_nodeset, false) }.ok()
+        unsafe { Bitmap::copy_from_raw((*self.ptr).complete_nodeset) }.ok()
     }
 
     /// Array of stringified info type=name.
@@ -663,3 +696,51 @@ pub enum Attributes<'topo> {
     PciDev(PciDevAttributes<'topo>),
     Bridge(BridgeAttributes<'topo>),
 }
+
+/// Identifies an object of a [`Topology`] by its depth and logical index, without borrowing it.
+///
+/// This allows referring to objects while the [`Topology`] is (mutably) borrowed, e.g., for
+/// adding distances between them via [`Topology::add_distances`]; an `ObjectId` may be resolved
+/// back to an [`Object`] via [`Topology::object_by_id`].
+///
+/// An `ObjectId` is only valid as long as the structure of the topology does not change: depths
+/// and logical indexes are renumbered when Group levels are inserted (e.g., by committing
+/// distances with [`DistancesAddFlags::GROUP`]) or when objects are removed (e.g., by
+/// [`Topology::restrict`]), after which an `ObjectId` may refer to another object, or to none.
+///
+/// [`Topology`]: crate::topology::Topology
+/// [`Topology::add_distances`]: crate::topology::Topology::add_distances
+/// [`Topology::object_by_id`]: crate::topology::Topology::object_by_id
+/// [`DistancesAddFlags::GROUP`]: crate::distances::DistancesAddFlags::GROUP
+/// [`Topology::restrict`]: crate::topology::Topology::restrict
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObjectId {
+    depth: i32,
+    logical_index: u32,
+}
+
+impl ObjectId {
+    /// Identify the object with the given logical index at depth `depth`.
+    pub fn new(depth: i32, logical_index: u32) -> Self {
+        Self {
+            depth,
+            logical_index,
+        }
+    }
+
+    /// Depth of the object.
+    pub fn depth(&self) -> i32 {
+        self.depth
+    }
+
+    /// Logical index of the object among the objects at its depth.
+    pub fn logical_index(&self) -> u32 {
+        self.logical_index
+    }
+}
+
+impl From<Object<'_>> for ObjectId {
+    fn from(obj: Object<'_>) -> Self {
+        Self::new(obj.depth(), obj.logical_index())
+    }
+}
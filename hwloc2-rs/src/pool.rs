@@ -0,0 +1,178 @@
+//! [`rayon`] thread pools whose workers are bound to topology objects.
+//!
+//! Available with the `rayon` feature.
+
+use std::sync::{mpsc, Arc, Mutex, PoisonError};
+
+use crate::{
+    binding::{CpuBindFlags, MemBindFlags, MemBindPolicy, MemBindSet},
+    bitmap::{CpuSet, NodeSet},
+    error::Error,
+    topology::Topology,
+    types::{ObjectType, TypeDepth},
+};
+
+/// Builder for [`rayon::ThreadPool`]s with one worker per object of a given type (e.g., one per
+/// PU, Core, L3 cache or NUMA node), each bound on the CPUs of its object.
+///
+/// Each worker binds itself in the `start_handler` of the pool, i.e., before it runs any task,
+/// and [`PinnedPoolBuilder::build`] only returns once all of them have done so.
+///
+/// ```no_run
+/// # use std::sync::Arc;
+/// # use hwloc2::{pool::PinnedPoolBuilder, ObjectType, Topology};
+/// use rayon::prelude::*;
+///
+/// let topo = Arc::new(Topology::builder()?.build()?);
+/// let pool = PinnedPoolBuilder::new(topo, ObjectType::Core)
+///     .singlify(true)
+///     .build()?;
+/// let sum: u64 = pool.install(|| (0..1_000_000u64).into_par_iter().sum());
+/// # Ok::<(), hwloc2::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct PinnedPoolBuilder {
+    topo: Arc<Topology>,
+    obj_type: ObjectType,
+    singlify: bool,
+    membind: Option<MemBindPolicy>,
+    builder: rayon::ThreadPoolBuilder,
+}
+
+impl PinnedPoolBuilder {
+    /// Create a new builder for a pool with one worker per object of type `obj_type`.
+    pub fn new(topo: Arc<Topology>, obj_type: ObjectType) -> Self {
+        Self {
+            topo,
+            obj_type,
+            singlify: false,
+            membind: None,
+            builder: rayon::ThreadPoolBuilder::new(),
+        }
+    }
+
+    /// Bind each worker on a single PU of its object, rather than on all of them, so that the OS
+    /// does not migrate it among them; see [`Bitmap::singlify`].
+    ///
+    /// [`Bitmap::singlify`]: crate::bitmap::Bitmap::singlify
+    pub fn singlify(mut self, singlify: bool) -> Self {
+        self.singlify = singlify;
+        self
+    }
+
+    /// Also bind the memory of each worker to the NUMA node(s) of its object (or, if it has no
+    /// nodeset, to the NUMA nodes that are local to its CPUs), according to `policy`.
+    pub fn membind(mut self, policy: MemBindPolicy) -> Self {
+        self.membind = Some(policy);
+        self
+    }
+
+    /// Name the worker threads; see [`rayon::ThreadPoolBuilder::thread_name`].
+    ///
+    /// The index of each worker is also the logical index of its object.
+    pub fn thread_name<F>(mut self, closure: F) -> Self
+    where
+        F: FnMut(usize) -> String + 'static,
+    {
+        self.builder = self.builder.thread_name(closure);
+        self
+    }
+
+    /// Set the size of the stack of the worker threads; see
+    /// [`rayon::ThreadPoolBuilder::stack_size`].
+    pub fn stack_size(mut self, stack_size: usize) -> Self {
+        self.builder = self.builder.stack_size(stack_size);
+        self
+    }
+
+    /// Create the thread pool, once all of its workers have been bound.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::ThreadPoolEmpty`] if there are no objects of the given type.
+    /// - [`Error::ThreadPoolMultipleDepths`] if objects of the given type exist at multiple
+    ///   depths (e.g., Groups).
+    /// - [`Error::ObjectWithoutCpuSet`] if the objects have no CPU set (i.e., for Misc and I/O
+    ///   objects).
+    /// - [`Error::BitmapSinglify`] if a CPU set cannot be singlified.
+    /// - [`Error::ThreadPoolBuild`] if `rayon` fails to create the pool.
+    /// - [`Error::CpuBindSet`] or [`Error::MemBindSet`] if a worker cannot be bound.
+    ///
+    /// [`Error::ThreadPoolEmpty`]: crate::error::Error::ThreadPoolEmpty
+    /// [`Error::ThreadPoolMultipleDepths`]: crate::error::Error::ThreadPoolMultipleDepths
+    /// [`Error::ObjectWithoutCpuSet`]: crate::error::Error::ObjectWithoutCpuSet
+    /// [`Error::BitmapSinglify`]: crate::error::Error::BitmapSinglify
+    /// [`Error::ThreadPoolBuild`]: crate::error::Error::ThreadPoolBuild
+    /// [`Error::CpuBindSet`]: crate::error::Error::CpuBindSet
+    /// [`Error::MemBindSet`]: crate::error::Error::MemBindSet
+    pub fn build(self) -> Result<rayon::ThreadPool, Error> {
+        let depth = match self.topo.type_depth(self.obj_type) {
+            d if d == TypeDepth::Unknown as i32 => {
+                return Err(Error::ThreadPoolEmpty(self.obj_type))
+            }
+            d if d == TypeDepth::Multiple as i32 => {
+                return Err(Error::ThreadPoolMultipleDepths(self.obj_type))
+            }
+            depth => depth,
+        };
+        let bindings = (0..self.topo.nbobjs_by_depth(depth))
+            .filter_map(|idx| self.topo.object_by_depth(depth, idx))
+            .map(|obj| {
+                let mut cpuset = obj.cpuset().ok_or(Error::ObjectWithoutCpuSet)?;
+                if self.singlify {
+                    cpuset.singlify()?;
+                }
+                Ok(WorkerBinding {
+                    cpuset,
+                    nodeset: obj.nodeset(),
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        if bindings.is_empty() {
+            return Err(Error::ThreadPoolEmpty(self.obj_type));
+        }
+
+        let num_threads = bindings.len();
+        let (tx, rx) = mpsc::channel();
+        let tx = Mutex::new(tx);
+        let (topo, membind) = (self.topo, self.membind);
+        let pool = self
+            .builder
+            .num_threads(num_threads)
+            .start_handler(move |index| {
+                let result = bindings[index].apply(&topo, membind);
+                // The receiver is only gone once `build()` has already failed.
+                let _ = tx
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .send(result);
+            })
+            .build()
+            .map_err(Error::ThreadPoolBuild)?;
+        for result in rx.iter().take(num_threads) {
+            result?;
+        }
+        Ok(pool)
+    }
+}
+
+/// The sets that a single worker binds itself to.
+struct WorkerBinding {
+    cpuset: CpuSet,
+    nodeset: Option<NodeSet>,
+}
+
+impl WorkerBinding {
+    /// Bind the calling thread on `self.cpuset` and, optionally, its memory to `self.nodeset`.
+    fn apply(&self, topo: &Topology, membind: Option<MemBindPolicy>) -> Result<(), Error> {
+        topo.set_cpubind(&self.cpuset, CpuBindFlags::THREAD)?;
+        if let Some(policy) = membind {
+            let set = match &self.nodeset {
+                Some(nodeset) => MemBindSet::NodeSet(nodeset),
+                None => MemBindSet::CpuSet(&self.cpuset),
+            };
+            topo.set_membind(set, policy, MemBindFlags::THREAD)?;
+        }
+        Ok(())
+    }
+}
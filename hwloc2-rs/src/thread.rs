@@ -0,0 +1,232 @@
+//! Spawning threads that are bound to topology objects.
+//!
+//! A thread spawned through a [`PinnedBuilder`] (or [`Topology::spawn_on`]) is bound on the CPUs
+//! of the given object and, optionally, has its memory bound to the object's NUMA node(s). That
+//! is, both bindings are in place before the thread runs the given closure, and thus before any
+//! of its allocations.
+//!
+//! The new thread binds itself, before running the closure, and reports the outcome to the
+//! spawning thread, whose own bindings are left untouched; spawning only returns once the new
+//! thread has been bound, and it fails if the new thread could not be bound, in which case the
+//! closure is never run.
+//!
+//! [`Topology::spawn_on`]: crate::topology::Topology::spawn_on
+
+use std::{
+    io, panic,
+    sync::mpsc,
+    thread::{self, JoinHandle, Scope, ScopedJoinHandle},
+};
+
+use crate::{
+    binding::{CpuBindFlags, MemBindFlags, MemBindPolicy, MemBindSet},
+    bitmap::{CpuSet, NodeSet},
+    error::Error,
+    object::Object,
+    topology::Topology,
+};
+
+/// Builder for threads that are bound to a topology object.
+///
+/// It wraps a [`std::thread::Builder`], whose configuration (i.e., name and stack size) is
+/// available through the respective methods.
+///
+/// ```no_run
+/// # use hwloc2::{binding::MemBindPolicy, thread::PinnedBuilder, ObjectType, Topology};
+/// let topo = Topology::builder()?.build()?;
+/// let core = topo.object_by_type(ObjectType::Core, 0).unwrap();
+/// let handle = PinnedBuilder::new(&topo, core)?
+///     .name("worker-0".to_string())
+///     .singlify(true)
+///     .membind(MemBindPolicy::Bind)
+///     .spawn(|| vec![0u8; 1 << 20].len())?;
+/// assert_eq!(1 << 20, handle.join().unwrap());
+/// # Ok::<(), hwloc2::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct PinnedBuilder<'topo> {
+    topo: &'topo Topology,
+    builder: thread::Builder,
+    cpuset: CpuSet,
+    nodeset: Option<NodeSet>,
+    singlify: bool,
+    membind: Option<MemBindPolicy>,
+}
+
+impl<'topo> PinnedBuilder<'topo> {
+    /// Create a new builder for threads that are bound on the CPUs of `obj`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ObjectWithoutCpuSet`] if `obj` has no CPU set (i.e., for Misc and I/O
+    /// objects).
+    ///
+    /// [`Error::ObjectWithoutCpuSet`]: crate::error::Error::ObjectWithoutCpuSet
+    pub fn new(topo: &'topo Topology, obj: Object<'_>) -> Result<Self, Error> {
+        Ok(Self {
+            topo,
+            builder: thread::Builder::new(),
+            cpuset: obj.cpuset().ok_or(Error::ObjectWithoutCpuSet)?,
+            nodeset: obj.nodeset(),
+            singlify: false,
+            membind: None,
+        })
+    }
+
+    /// Name the thread; see [`std::thread::Builder::name`].
+    pub fn name(mut self, name: String) -> Self {
+        self.builder = self.builder.name(name);
+        self
+    }
+
+    /// Set the size of the stack of the thread; see [`std::thread::Builder::stack_size`].
+    pub fn stack_size(mut self, size: usize) -> Self {
+        self.builder = self.builder.stack_size(size);
+        self
+    }
+
+    /// Bind the thread on a single PU of the object, rather than on all of them, so that the OS
+    /// does not migrate it among them; see [`Bitmap::singlify`].
+    ///
+    /// [`Bitmap::singlify`]: crate::bitmap::Bitmap::singlify
+    pub fn singlify(mut self, singlify: bool) -> Self {
+        self.singlify = singlify;
+        self
+    }
+
+    /// Also bind the memory of the thread to the NUMA node(s) of the object (or, if it has no
+    /// nodeset, to the NUMA nodes that are local to its CPUs), according to `policy`.
+    pub fn membind(mut self, policy: MemBindPolicy) -> Self {
+        self.membind = Some(policy);
+        self
+    }
+
+    /// Spawn a new, bound thread, returning a [`JoinHandle`] for it.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::BitmapSinglify`] if the CPU set cannot be singlified.
+    /// - [`Error::ThreadSpawn`] if the OS fails to create the thread.
+    /// - [`Error::CpuBindSet`] if the new thread cannot be bound on the CPUs of the object.
+    /// - [`Error::MemBindSet`] if the memory of the new thread cannot be bound to the NUMA
+    ///   node(s) of the object.
+    ///
+    /// The new thread has already exited, without running `f`, when any of the latter two
+    /// errors is returned.
+    ///
+    /// [`Error::BitmapSinglify`]: crate::error::Error::BitmapSinglify
+    /// [`Error::ThreadSpawn`]: crate::error::Error::ThreadSpawn
+    /// [`Error::CpuBindSet`]: crate::error::Error::CpuBindSet
+    /// [`Error::MemBindSet`]: crate::error::Error::MemBindSet
+    pub fn spawn<F, T>(self, f: F) -> Result<JoinHandle<T>, Error>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        self.spawn_with(
+            f,
+            |builder, pinned| builder.spawn(move || pinned.run()),
+            |handle| drop(handle.join()),
+        )
+    }
+
+    /// Spawn a new, bound, scoped thread, returning a [`ScopedJoinHandle`] for it.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`PinnedBuilder::spawn`].
+    pub fn spawn_scoped<'scope, 'env, F, T>(
+        self,
+        scope: &'scope Scope<'scope, 'env>,
+        f: F,
+    ) -> Result<ScopedJoinHandle<'scope, T>, Error>
+    where
+        F: FnOnce() -> T + Send + 'scope,
+        T: Send + 'scope,
+    {
+        self.spawn_with(
+            f,
+            |builder, pinned| builder.spawn_scoped(scope, move || pinned.run()),
+            |handle| drop(handle.join()),
+        )
+    }
+
+    /// Spawn the new thread through `spawn`, running `f` once it has bound itself, and wait
+    /// for the outcome of the binding; if it failed, the thread is joined through `join` (as it
+    /// exits right away), and the error is returned instead of its handle.
+    fn spawn_with<F, H>(
+        mut self,
+        f: F,
+        spawn: impl FnOnce(thread::Builder, PinnedFn<F>) -> io::Result<H>,
+        join: impl FnOnce(H),
+    ) -> Result<H, Error> {
+        if self.singlify {
+            self.cpuset.singlify()?;
+        }
+        let (tx, rx) = mpsc::channel();
+        let pinned = PinnedFn {
+            topo: self.topo,
+            cpuset: self.cpuset,
+            membind: self.membind.map(|policy| (self.nodeset, policy)),
+            tx,
+            f,
+        };
+        let handle = spawn(self.builder, pinned).map_err(Error::ThreadSpawn)?;
+        // `self.topo` must remain borrowed until the new thread is done with it, i.e., until it
+        // has reported the outcome of its binding (or dropped the sender by unwinding).
+        match rx.recv().unwrap_or(Err(Error::CpuBindSet)) {
+            Ok(()) => Ok(handle),
+            Err(err) => {
+                join(handle);
+                Err(err)
+            }
+        }
+    }
+}
+
+/// The closure run by a pinned thread, which binds the thread before running `f`.
+struct PinnedFn<F> {
+    topo: *const Topology,
+    cpuset: CpuSet,
+    membind: Option<(Option<NodeSet>, MemBindPolicy)>,
+    tx: mpsc::Sender<Result<(), Error>>,
+    f: F,
+}
+
+// SAFETY: `Topology` is `Sync`, and `topo` is only dereferenced while the spawning thread waits
+// for the outcome of the binding, during which it keeps the topology borrowed (see
+// `PinnedBuilder::spawn_with`). The other fields are `Send` as long as `F` is.
+unsafe impl<F: Send> Send for PinnedFn<F> {}
+
+impl<F> PinnedFn<F> {
+    /// Bind the current thread, report the outcome to the spawning thread, and then run `f`.
+    fn run<T>(self) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        // SAFETY: The spawning thread keeps the topology borrowed until the outcome is sent.
+        let result = self.bind(unsafe { &*self.topo });
+        let bound = result.is_ok();
+        // The spawning thread waits for the outcome, so the receiver cannot be gone.
+        let _ = self.tx.send(result);
+        if !bound {
+            // Exit without running `f`, and without invoking the panic hook; the spawning thread
+            // joins this thread and returns the error instead.
+            panic::resume_unwind(Box::new(()));
+        }
+        (self.f)()
+    }
+
+    /// Bind the current thread on `self.cpuset` and, optionally, its memory.
+    fn bind(&self, topo: &Topology) -> Result<(), Error> {
+        topo.set_cpubind(&self.cpuset, CpuBindFlags::THREAD)?;
+        if let Some((nodeset, policy)) = &self.membind {
+            let set = match nodeset {
+                Some(nodeset) => MemBindSet::NodeSet(nodeset),
+                None => MemBindSet::CpuSet(&self.cpuset),
+            };
+            topo.set_membind(set, *policy, MemBindFlags::THREAD)?;
+        }
+        Ok(())
+    }
+}
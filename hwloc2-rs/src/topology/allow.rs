@@ -0,0 +1,41 @@
+use crate::bitmap::{CpuSet, NodeSet};
+
+/// The sets of PUs and NUMA nodes to be marked as allowed by [`Topology::allow`].
+///
+/// [`Topology::allow`]: crate::topology::Topology::allow
+#[derive(Debug, Clone, Copy)]
+pub enum Allow<'a> {
+    /// Mark all objects as allowed in the topology.
+    All,
+
+    /// Only allow objects that are available to the current process.
+    ///
+    /// The topology must have [`Flags::IS_THISSYSTEM`] so that the set of available resources
+    /// can actually be retrieved from the operating system.
+    ///
+    /// [`Flags::IS_THISSYSTEM`]: crate::topology::flags::Flags::IS_THISSYSTEM
+    LocalRestrictions,
+
+    /// Allow a custom set of objects.
+    ///
+    /// If `cpuset` is `Some`, the set of allowed PUs is changed to the given CPU set; if `nodeset`
+    /// is `Some`, the set of allowed NUMA nodes is changed to the given nodeset. At least one of
+    /// them must be `Some`.
+    Custom {
+        cpuset: Option<&'a CpuSet>,
+        nodeset: Option<&'a NodeSet>,
+    },
+}
+
+impl Allow<'_> {
+    /// The corresponding `hwloc_allow_flags_e` value.
+    pub(super) fn flags(&self) -> u64 {
+        (match self {
+            Allow::All => hwloc2_sys::hwloc_allow_flags_e_HWLOC_ALLOW_FLAG_ALL,
+            Allow::LocalRestrictions => {
+                hwloc2_sys::hwloc_allow_flags_e_HWLOC_ALLOW_FLAG_LOCAL_RESTRICTIONS
+            }
+            Allow::Custom { .. } => hwloc2_sys::hwloc_allow_flags_e_HWLOC_ALLOW_FLAG_CUSTOM,
+        }) as u64
+    }
+}
@@ -0,0 +1,135 @@
+use std::{ffi::CStr, ptr::NonNull};
+
+use super::Topology;
+use crate::error::Error;
+
+bitflags::bitflags! {
+    /// Flags for exporting XML topologies.
+    ///
+    /// Flags should be given to [`Topology::export_xml`] and [`Topology::to_xml_string`].
+    ///
+    /// [`Topology::export_xml`]: crate::topology::Topology::export_xml
+    /// [`Topology::to_xml_string`]: crate::topology::Topology::to_xml_string
+    #[derive(Default)]
+    #[repr(C)]
+    pub struct XmlExportFlags: u64 {
+        /// Export XML that is loadable by hwloc v1.x.
+        ///
+        /// However, the export may miss some details about the topology.
+        const V1 = hwloc2_sys::hwloc_topology_export_xml_flags_e_HWLOC_TOPOLOGY_EXPORT_XML_FLAG_V1
+            as u64;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags for exporting synthetic topologies.
+    ///
+    /// Flags should be given to [`Topology::to_synthetic`].
+    ///
+    /// [`Topology::to_synthetic`]: crate::topology::Topology::to_synthetic
+    #[derive(Default)]
+    #[repr(C)]
+    pub struct SyntheticExportFlags: u64 {
+        /// Export extended types such as L2dcache as basic types such as Cache.
+        ///
+        /// This is required if loading the synthetic description with hwloc < 1.9.
+        const NO_EXTENDED_TYPES =
+            hwloc2_sys::hwloc_topology_export_synthetic_flags_e_HWLOC_TOPOLOGY_EXPORT_SYNTHETIC_FLAG_NO_EXTENDED_TYPES
+                as u64;
+        /// Do not export level attributes.
+        ///
+        /// Ignore level attributes such as memory/cache sizes or PU indexes. This is required if
+        /// loading the synthetic description with hwloc < 1.10.
+        const NO_ATTRS =
+            hwloc2_sys::hwloc_topology_export_synthetic_flags_e_HWLOC_TOPOLOGY_EXPORT_SYNTHETIC_FLAG_NO_ATTRS
+                as u64;
+        /// Export the memory hierarchy as expected in hwloc 1.x.
+        ///
+        /// Instead of attaching memory children to levels, export single NUMA node child as
+        /// normal intermediate levels, when possible. This is required if loading the synthetic
+        /// description with hwloc 1.x. However, this may fail if some objects have multiple local
+        /// NUMA nodes.
+        const V1 =
+            hwloc2_sys::hwloc_topology_export_synthetic_flags_e_HWLOC_TOPOLOGY_EXPORT_SYNTHETIC_FLAG_V1
+                as u64;
+        /// Do not export memory information.
+        ///
+        /// Only export the actual hierarchy of normal CPU-side objects and ignore where memory is
+        /// attached. This is useful for when the hierarchy of CPUs is what really matters, but it
+        /// behaves as if there was a single machine-wide NUMA node.
+        const IGNORE_MEMORY =
+            hwloc2_sys::hwloc_topology_export_synthetic_flags_e_HWLOC_TOPOLOGY_EXPORT_SYNTHETIC_FLAG_IGNORE_MEMORY
+                as u64;
+    }
+}
+
+/// An XML buffer allocated by hwloc, which is freed through hwloc when dropped.
+pub(super) struct XmlBuffer<'topo> {
+    topo: &'topo Topology,
+    ptr: NonNull<libc::c_char>,
+}
+
+impl<'topo> XmlBuffer<'topo> {
+    /// Export `topo` into a newly-allocated XML memory buffer.
+    pub(super) fn export(topo: &'topo Topology, flags: XmlExportFlags) -> Result<Self, Error> {
+        let mut ptr = std::ptr::null_mut();
+        let mut buflen = 0;
+        // SAFETY: `topo.topo` is a valid, loaded topology, and both out-pointers are valid for
+        // writes. On success, hwloc allocates the buffer, which remains valid until it is passed
+        // to `hwloc_free_xmlbuffer()` (in `impl Drop`).
+        if -1
+            == unsafe {
+                hwloc2_sys::hwloc_topology_export_xmlbuffer(
+                    topo.topo,
+                    &mut ptr,
+                    &mut buflen,
+                    flags.bits(),
+                )
+            }
+        {
+            return Err(Error::TopologyExportXmlBuffer);
+        }
+        Ok(Self {
+            topo,
+            ptr: NonNull::new(ptr).ok_or(Error::TopologyExportXmlBuffer)?,
+        })
+    }
+
+    /// The contents of the buffer, as a C string.
+    pub(super) fn as_c_str(&self) -> &CStr {
+        // SAFETY: The buffer was allocated by hwloc as a NUL-terminated C string, and it remains
+        // valid (and unmodified) until `self` is dropped.
+        unsafe { CStr::from_ptr(self.ptr.as_ptr()) }
+    }
+}
+
+impl Drop for XmlBuffer<'_> {
+    fn drop(&mut self) {
+        // SAFETY: `self.ptr` was allocated by `hwloc_topology_export_xmlbuffer()` for the same
+        // topology, and it is freed only once, here.
+        unsafe { hwloc2_sys::hwloc_free_xmlbuffer(self.topo.topo, self.ptr.as_ptr()) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SyntheticExportFlags, XmlExportFlags};
+
+    #[test]
+    fn xml_export_flags() {
+        assert!(XmlExportFlags::default().is_empty());
+    }
+
+    #[test]
+    fn synthetic_export_flags() {
+        assert!(SyntheticExportFlags::default().is_empty());
+        assert_eq!(
+            0b1111,
+            (SyntheticExportFlags::NO_EXTENDED_TYPES
+                | SyntheticExportFlags::NO_ATTRS
+                | SyntheticExportFlags::V1
+                | SyntheticExportFlags::IGNORE_MEMORY)
+                .bits()
+        );
+    }
+}
@@ -152,13 +152,63 @@ bitflags::bitflags! {
     }
 }
 
+bitflags::bitflags! {
+    /// Flags to be given to [`Topology::restrict`].
+    ///
+    /// [`Topology::restrict`]: crate::topology::Topology::restrict
+    #[derive(Default)]
+    #[repr(C)]
+    pub struct RestrictFlags: u64 {
+        /// Remove all objects that became CPU-less.
+        ///
+        /// By default, only objects that contain no PU and no memory are removed. This flag may
+        /// not be used with [`RestrictFlags::BYNODESET`].
+        const REMOVE_CPULESS = hwloc2_sys::hwloc_restrict_flags_e_HWLOC_RESTRICT_FLAG_REMOVE_CPULESS
+            as u64;
+
+        /// Restrict by nodeset instead of CPU set.
+        ///
+        /// Only keep objects whose nodeset is included or partially included in the given set.
+        /// This flag may not be used with [`RestrictFlags::REMOVE_CPULESS`].
+        const BYNODESET = hwloc2_sys::hwloc_restrict_flags_e_HWLOC_RESTRICT_FLAG_BYNODESET as u64;
+
+        /// Remove all objects that became Memory-less.
+        ///
+        /// By default, only objects that contain no PU and no memory are removed. This flag may
+        /// only be used with [`RestrictFlags::BYNODESET`].
+        const REMOVE_MEMLESS = hwloc2_sys::hwloc_restrict_flags_e_HWLOC_RESTRICT_FLAG_REMOVE_MEMLESS
+            as u64;
+
+        /// Move Misc objects to ancestors if their parents are removed during restriction.
+        ///
+        /// If this flag is not set, Misc objects are removed when their parents are removed.
+        const ADAPT_MISC = hwloc2_sys::hwloc_restrict_flags_e_HWLOC_RESTRICT_FLAG_ADAPT_MISC as u64;
+
+        /// Move I/O objects to ancestors if their parents are removed during restriction.
+        ///
+        /// If this flag is not set, I/O devices and bridges are removed when their parents are
+        /// removed.
+        const ADAPT_IO = hwloc2_sys::hwloc_restrict_flags_e_HWLOC_RESTRICT_FLAG_ADAPT_IO as u64;
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Flags;
+    use super::{Flags, RestrictFlags};
 
     #[test]
     fn flags() {
         let f = Flags::default();
         assert!(f.is_empty());
     }
+
+    #[test]
+    fn restrict_flags() {
+        assert!(RestrictFlags::default().is_empty());
+        assert_eq!(
+            0b11111,
+            RestrictFlags::all().bits(),
+            "RestrictFlags should map to distinct hwloc bits"
+        );
+    }
 }
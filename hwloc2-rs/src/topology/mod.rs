@@ -1,20 +1,39 @@
-use std::ptr;
+use std::{
+    ffi::CString,
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+    ptr,
+    thread::JoinHandle,
+};
 
+pub mod allow;
+pub mod export;
 pub mod filters;
 pub mod flags;
 pub mod support;
+pub mod synthetic;
 
+pub use allow::Allow;
+pub use export::{SyntheticExportFlags, XmlExportFlags};
 pub use filters::Filter;
-pub use flags::Flags;
+pub use flags::{Flags, RestrictFlags};
 pub use support::Support;
+pub use synthetic::{SyntheticDescription, SyntheticLevel};
 
 use num_traits::FromPrimitive;
 
 use crate::{
+    binding::{
+        CpuBindFlags, CpuBindGuard, MemBindFlags, MemBindGuard, MemBindPolicy, MemBindSet,
+        ThreadHandle,
+    },
     bitmap::{Bitmap, CpuSet, NodeSet},
+    distances::{Distances, DistancesBuilder, DistancesKind},
     error::Error,
-    object::{Attributes, Object},
+    memattr::{self, Initiator, MemAttr, MemAttrFlags, MemAttrId},
+    object::{Attributes, Object, ObjectId},
     ptr_mut_to_const,
+    thread::PinnedBuilder,
     types::{BridgeType, ObjectType, TypeDepth},
 };
 
@@ -25,8 +44,18 @@ pub struct Topology {
 }
 
 // Thread Safety in hwloc-2.7.1: https://www.open-mpi.org/projects/hwloc/doc/v2.7.1/a00370.php
-//unsafe impl Sync for Topology {}
-//unsafe impl Send for Topology {}
+//
+// SAFETY: hwloc's query functions may be invoked concurrently from several threads on a loaded
+// topology, as long as its internal caches are up to date; these are exposed through `&self`
+// methods. Functions that modify the topology (e.g., restricting it, or changing its distances or
+// memory attributes) are exposed through `&mut self` methods, and may invalidate these caches,
+// which hwloc would otherwise rebuild lazily (and not thread-safely) on the next query. This is
+// why every such method ends with `Topology::refresh`, so that the caches are always up to date
+// once the exclusive borrow ends. Binding functions may also be invoked concurrently, since they
+// only access the topology in a read-only fashion. Finally, the topology is not tied to the
+// thread that created it, so it may also be destroyed by any other thread.
+unsafe impl Send for Topology {}
+unsafe impl Sync for Topology {}
 
 impl Topology {
     /// Create a new [`TopologyBuilder`] to configure and create a new [`Topology`].
@@ -43,7 +72,41 @@ impl Topology {
         // SAFETY: `topo` is a freshly allocated pointer, of the correct type, set to NULL.
         match unsafe { hwloc2_sys::hwloc_topology_init(&mut topo) } {
             -1 => Err(Error::TopologyInit),
-            _ => Ok(TopologyBuilder { topo, built: false }),
+            _ => Ok(TopologyBuilder {
+                topo,
+                settings: Vec::new(),
+                built: false,
+            }),
+        }
+    }
+
+    /// Duplicate the topology.
+    ///
+    /// The entire topology structure as well as its objects are duplicated into a new,
+    /// independent one, e.g., to keep a pristine copy around while modifying another.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::TopologyDup`] if hwloc fails to duplicate the topology.
+    /// - [`Error::TopologySupport`] if hwloc fails to retrieve the support of the duplicate.
+    ///
+    /// [`Error::TopologyDup`]: crate::error::Error::TopologyDup
+    /// [`Error::TopologySupport`]: crate::error::Error::TopologySupport
+    pub fn try_clone(&self) -> Result<Self, Error> {
+        let mut topo = ptr::null_mut();
+        // SAFETY: `topo` is a freshly allocated pointer, of the correct type, set to NULL, and
+        // `self.topo` is a valid, loaded topology.
+        if -1 == unsafe { hwloc2_sys::hwloc_topology_dup(&mut topo, self.topo) } {
+            return Err(Error::TopologyDup);
+        }
+        match Support::try_new(topo) {
+            Ok(support) => Ok(Self { topo, support }),
+            Err(err) => {
+                // SAFETY: `topo` was successfully allocated right above, and it is not used
+                // anywhere else.
+                unsafe { hwloc2_sys::hwloc_topology_destroy(topo) };
+                Err(err)
+            }
         }
     }
 
@@ -71,6 +134,30 @@ impl Topology {
         &self.support
     }
 
+    /// The underlying hwloc topology, for wrappers that live outside of this module.
+    pub(crate) fn as_ptr(&self) -> *mut hwloc2_sys::hwloc_topology {
+        self.topo
+    }
+
+    /// Refresh the internal caches of the topology (e.g., of distances and memory attributes)
+    /// that hwloc invalidates when the topology is modified.
+    ///
+    /// This must be called at the end of every method that modifies the topology, so that it may
+    /// then be queried concurrently; see the `Sync` implementation of `Topology`.
+    ///
+    /// # Panics
+    ///
+    /// If hwloc fails to refresh the topology, since it could not safely be shared otherwise.
+    pub(crate) fn refresh(&mut self) {
+        // SAFETY: `self.topo` is a valid, loaded topology, which is exclusively borrowed.
+        if unsafe { hwloc2_sys::hwloc_topology_refresh(self.topo) } == -1 {
+            panic!(
+                "failed to refresh the Topology: {}",
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+
     /// Retrieve the OR'ed flags of the topology.
     ///
     /// # Note
@@ -293,6 +380,11 @@ impl Topology {
         Some(unsafe { Object::new(ptr_mut_to_const(obj)) })
     }
 
+    /// Returns the object identified by `id`, if it exists in this topology.
+    pub fn object_by_id(&self, id: ObjectId) -> Option<Object<'_>> {
+        self.object_by_depth(id.depth(), id.logical_index())
+    }
+
     /// Returns the topology object at logical index `idx` with type `obj_type`.
     ///
     /// If no object for that type exists, `None` is returned. If there are several levels with
@@ -387,12 +479,12 @@ impl Topology {
     /// [`ObjectType::NumaNode`]: crate::types::ObjectType::NumaNode
     // Implementation port from C (file `include/hwloc/helper.h`).
     pub fn numanode_object_by_os_index<'o, 't: 'o>(&'t self, os_index: u32) -> Option<Object<'o>> {
-        let mut ret = None;
-        while let Some(obj) = self.next_object_by_type(ObjectType::NumaNode, ret) {
+        let mut prev = None;
+        while let Some(obj) = self.next_object_by_type(ObjectType::NumaNode, prev) {
             if obj.os_index() == os_index {
-                return ret;
+                return Some(obj);
             }
-            ret.replace(obj);
+            prev.replace(obj);
         }
         None
     }
@@ -409,12 +501,12 @@ impl Topology {
     /// [`ObjectType::PU`]: crate::types::ObjectType::PU
     // Implementation port from C (file `include/hwloc/helper.h`).
     pub fn pu_object_by_os_index<'o, 't: 'o>(&'t self, os_index: u32) -> Option<Object<'o>> {
-        let mut ret = None;
-        while let Some(obj) = self.next_object_by_type(ObjectType::PU, ret) {
+        let mut prev = None;
+        while let Some(obj) = self.next_object_by_type(ObjectType::PU, prev) {
             if obj.os_index() == os_index {
-                return ret;
+                return Some(obj);
             }
-            ret.replace(obj);
+            prev.replace(obj);
         }
         None
     }
@@ -427,26 +519,623 @@ impl Topology {
     /////
     ///////////////////////////////////////////////////////////////////////////////////////////////
 
-    /// TODO: UNTESTED
-    ///
     /// Bind current process or thread on CPUs given in physical bitmap set.
     ///
     /// # Errors
     ///
-    /// Returns [`Error::CpuBindSet`] in case of failure.
+    /// Returns [`Error::CpuBindSet`] in case of failure (e.g., if the action is not supported, or
+    /// if the binding cannot be enforced).
     ///
     /// [`Error::CpuBindSet`]: crate::error::Error::CpuBindSet
-    pub fn set_cpubind(&self, cpuset: CpuSet, flags: i32) -> Result<(), Error> {
-        match unsafe { hwloc2_sys::hwloc_set_cpubind(self.topo, cpuset.as_ptr(), flags as i32) } {
+    pub fn set_cpubind(&self, cpuset: &CpuSet, flags: CpuBindFlags) -> Result<(), Error> {
+        // SAFETY: `self.topo` is a valid topology object, created via a `TopologyBuilder`, and
+        // `cpuset` is a valid bitmap; hwloc does not modify either of them.
+        match unsafe {
+            hwloc2_sys::hwloc_set_cpubind(
+                self.topo,
+                ptr_mut_to_const(cpuset.as_ptr()),
+                flags.bits(),
+            )
+        } {
             -1 => Err(Error::CpuBindSet),
             _ => Ok(()),
         }
     }
 
-    // TODO
-    //pub fn cpubind(&self, cpuset: CpuSet, flags: i32) {
-    //    unsafe { hwloc2_sys::hwloc_get_cpubind(self.topo, cpuset, flags) }
-    //}
+    /// Get current process or thread binding.
+    ///
+    /// Returns the CPU set of PUs which the process or thread (according to `flags`) was last
+    /// bound to.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::CpuBindGet`] in case of failure (e.g., if the action is not supported).
+    ///
+    /// [`Error::CpuBindGet`]: crate::error::Error::CpuBindGet
+    pub fn cpubind(&self, flags: CpuBindFlags) -> Result<CpuSet, Error> {
+        let cpuset = CpuSet::try_new_empty()?;
+        // SAFETY: `self.topo` is a valid topology object, created via a `TopologyBuilder`, and
+        // `cpuset` is a valid, exclusively owned bitmap.
+        match unsafe { hwloc2_sys::hwloc_get_cpubind(self.topo, cpuset.as_ptr(), flags.bits()) } {
+            -1 => Err(Error::CpuBindGet),
+            _ => Ok(cpuset),
+        }
+    }
+
+    /// Bind current process or thread on CPUs given in physical bitmap set, until the returned
+    /// guard is dropped.
+    ///
+    /// The current binding (according to [`CpuBindFlags::PROCESS`] and [`CpuBindFlags::THREAD`]
+    /// in `flags`) is retrieved first, and it is restored, with the same `flags`, when the guard
+    /// is dropped, or explicitly via [`CpuBindGuard::restore`].
+    ///
+    /// Unless [`CpuBindFlags::THREAD`] is given, the binding of the whole process is retrieved
+    /// with [`CpuBindFlags::STRICT`], so that restoring it cannot widen the binding of any of its
+    /// threads; this fails if they are not all bound alike.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::CpuBindGet`] if hwloc fails to retrieve the current binding, or if the threads
+    ///   of the process have different bindings.
+    /// - [`Error::CpuBindSet`] if hwloc fails to bind on `cpuset`.
+    ///
+    /// [`Error::CpuBindGet`]: crate::error::Error::CpuBindGet
+    /// [`Error::CpuBindSet`]: crate::error::Error::CpuBindSet
+    pub fn bind_scoped(
+        &self,
+        cpuset: &CpuSet,
+        flags: CpuBindFlags,
+    ) -> Result<CpuBindGuard<'_>, Error> {
+        CpuBindGuard::bind(self, cpuset, flags)
+    }
+
+    /// Spawn a new thread that is bound on the CPUs of `obj`, before it runs `f`.
+    ///
+    /// This is a shorthand for [`PinnedBuilder::new`] followed by [`PinnedBuilder::spawn`], which
+    /// also allow for binding a single PU of the object, or the memory of the thread.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`PinnedBuilder::new`] and [`PinnedBuilder::spawn`].
+    ///
+    /// [`PinnedBuilder::new`]: crate::thread::PinnedBuilder::new
+    /// [`PinnedBuilder::spawn`]: crate::thread::PinnedBuilder::spawn
+    pub fn spawn_on<F, T>(&self, obj: Object<'_>, f: F) -> Result<JoinHandle<T>, Error>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        PinnedBuilder::new(self, obj)?.spawn(f)
+    }
+
+    /// Bind process `pid` on CPUs given in physical bitmap set.
+    ///
+    /// As a special case on Linux, if a tid (thread ID) is supplied instead of a pid (process ID)
+    /// and [`CpuBindFlags::THREAD`] is passed in `flags`, the binding is applied to that specific
+    /// thread. On non-Linux systems, [`CpuBindFlags::THREAD`] can not be used in `flags`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ProcCpuBindSet`] in case of failure.
+    ///
+    /// [`Error::ProcCpuBindSet`]: crate::error::Error::ProcCpuBindSet
+    pub fn set_proc_cpubind(
+        &self,
+        pid: libc::pid_t,
+        cpuset: &CpuSet,
+        flags: CpuBindFlags,
+    ) -> Result<(), Error> {
+        // SAFETY: `self.topo` is a valid topology object, created via a `TopologyBuilder`, and
+        // `cpuset` is a valid bitmap; hwloc does not modify either of them.
+        match unsafe {
+            hwloc2_sys::hwloc_set_proc_cpubind(
+                self.topo,
+                pid,
+                ptr_mut_to_const(cpuset.as_ptr()),
+                flags.bits(),
+            )
+        } {
+            -1 => Err(Error::ProcCpuBindSet(pid)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Get the current physical binding of process `pid`.
+    ///
+    /// Returns the CPU set of PUs which the process was last bound to.
+    ///
+    /// As a special case on Linux, if a tid (thread ID) is supplied instead of a pid (process ID)
+    /// and [`CpuBindFlags::THREAD`] is passed in `flags`, the binding for that specific thread is
+    /// returned. On non-Linux systems, [`CpuBindFlags::THREAD`] can not be used in `flags`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ProcCpuBindGet`] in case of failure.
+    ///
+    /// [`Error::ProcCpuBindGet`]: crate::error::Error::ProcCpuBindGet
+    pub fn proc_cpubind(&self, pid: libc::pid_t, flags: CpuBindFlags) -> Result<CpuSet, Error> {
+        let cpuset = CpuSet::try_new_empty()?;
+        // SAFETY: `self.topo` is a valid topology object, created via a `TopologyBuilder`, and
+        // `cpuset` is a valid, exclusively owned bitmap.
+        match unsafe {
+            hwloc2_sys::hwloc_get_proc_cpubind(self.topo, pid, cpuset.as_ptr(), flags.bits())
+        } {
+            -1 => Err(Error::ProcCpuBindGet(pid)),
+            _ => Ok(cpuset),
+        }
+    }
+
+    /// Bind thread `thread` on CPUs given in physical bitmap set.
+    ///
+    /// [`CpuBindFlags::PROCESS`] can not be used in `flags`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ThreadCpuBindSet`] in case of failure.
+    ///
+    /// [`Error::ThreadCpuBindSet`]: crate::error::Error::ThreadCpuBindSet
+    pub fn set_thread_cpubind(
+        &self,
+        thread: ThreadHandle<'_>,
+        cpuset: &CpuSet,
+        flags: CpuBindFlags,
+    ) -> Result<(), Error> {
+        // SAFETY: `self.topo` is a valid topology object, created via a `TopologyBuilder`,
+        // `cpuset` is a valid bitmap that hwloc does not modify, and `thread` refers to a live
+        // thread of the current process (as guaranteed by `ThreadHandle`).
+        match unsafe {
+            hwloc2_sys::hwloc_set_thread_cpubind(
+                self.topo,
+                thread.as_raw() as _,
+                ptr_mut_to_const(cpuset.as_ptr()),
+                flags.bits(),
+            )
+        } {
+            -1 => Err(Error::ThreadCpuBindSet),
+            _ => Ok(()),
+        }
+    }
+
+    /// Get the current physical binding of thread `thread`.
+    ///
+    /// Returns the CPU set of PUs which the thread was last bound to.
+    ///
+    /// [`CpuBindFlags::PROCESS`] can not be used in `flags`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ThreadCpuBindGet`] in case of failure.
+    ///
+    /// [`Error::ThreadCpuBindGet`]: crate::error::Error::ThreadCpuBindGet
+    pub fn thread_cpubind(
+        &self,
+        thread: ThreadHandle<'_>,
+        flags: CpuBindFlags,
+    ) -> Result<CpuSet, Error> {
+        let cpuset = CpuSet::try_new_empty()?;
+        // SAFETY: `self.topo` is a valid topology object, created via a `TopologyBuilder`,
+        // `cpuset` is a valid, exclusively owned bitmap, and `thread` refers to a live thread of
+        // the current process (as guaranteed by `ThreadHandle`).
+        match unsafe {
+            hwloc2_sys::hwloc_get_thread_cpubind(
+                self.topo,
+                thread.as_raw() as _,
+                cpuset.as_ptr(),
+                flags.bits(),
+            )
+        } {
+            -1 => Err(Error::ThreadCpuBindGet),
+            _ => Ok(cpuset),
+        }
+    }
+
+    /// Get the last physical CPU where the current process or thread ran.
+    ///
+    /// Returns the CPU set of PUs which the process or thread (according to `flags`) last ran on.
+    ///
+    /// The operating system may move some tasks from one processor to another at any time
+    /// according to their binding, so this function may return something that is already
+    /// outdated.
+    ///
+    /// `flags` can include either [`CpuBindFlags::PROCESS`] or [`CpuBindFlags::THREAD`] to
+    /// specify whether the query should be for the whole process (union of all CPUs on which all
+    /// threads are running), or only the current thread. If the process is single-threaded,
+    /// `flags` can be empty to let hwloc use whichever method is available on the underlying OS.
+    ///
+    /// See also [`Topology::last_pu_location`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::LastCpuLocation`] in case of failure.
+    ///
+    /// [`Error::LastCpuLocation`]: crate::error::Error::LastCpuLocation
+    pub fn last_cpu_location(&self, flags: CpuBindFlags) -> Result<CpuSet, Error> {
+        let cpuset = CpuSet::try_new_empty()?;
+        // SAFETY: `self.topo` is a valid topology object, created via a `TopologyBuilder`, and
+        // `cpuset` is a valid, exclusively owned bitmap.
+        match unsafe {
+            hwloc2_sys::hwloc_get_last_cpu_location(self.topo, cpuset.as_ptr(), flags.bits())
+        } {
+            -1 => Err(Error::LastCpuLocation),
+            _ => Ok(cpuset),
+        }
+    }
+
+    /// Get the last physical CPU where process `pid` ran.
+    ///
+    /// Returns the CPU set of PUs which the process last ran on.
+    ///
+    /// The operating system may move some tasks from one processor to another at any time
+    /// according to their binding, so this function may return something that is already
+    /// outdated.
+    ///
+    /// As a special case on Linux, if a tid (thread ID) is supplied instead of a pid (process ID)
+    /// and [`CpuBindFlags::THREAD`] is passed in `flags`, the last CPU location of that specific
+    /// thread is returned. On non-Linux systems, [`CpuBindFlags::THREAD`] can not be used in
+    /// `flags`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ProcLastCpuLocation`] in case of failure.
+    ///
+    /// [`Error::ProcLastCpuLocation`]: crate::error::Error::ProcLastCpuLocation
+    pub fn proc_last_cpu_location(
+        &self,
+        pid: libc::pid_t,
+        flags: CpuBindFlags,
+    ) -> Result<CpuSet, Error> {
+        let cpuset = CpuSet::try_new_empty()?;
+        // SAFETY: `self.topo` is a valid topology object, created via a `TopologyBuilder`, and
+        // `cpuset` is a valid, exclusively owned bitmap.
+        match unsafe {
+            hwloc2_sys::hwloc_get_proc_last_cpu_location(
+                self.topo,
+                pid,
+                cpuset.as_ptr(),
+                flags.bits(),
+            )
+        } {
+            -1 => Err(Error::ProcLastCpuLocation(pid)),
+            _ => Ok(cpuset),
+        }
+    }
+
+    /// Get the PU object where the current process or thread last ran.
+    ///
+    /// This is [`Topology::last_cpu_location`] followed by [`Topology::pu_object_by_os_index`]
+    /// on the first PU of the returned CPU set; hence, when the location of a multithreaded
+    /// process is queried (e.g., with [`CpuBindFlags::PROCESS`]), only the PU with the lowest
+    /// physical index is returned.
+    ///
+    /// Returns `None` if the last location is empty, or if it is not part of the topology (e.g.,
+    /// if the topology has been restricted).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::LastCpuLocation`] in case of failure.
+    ///
+    /// [`Error::LastCpuLocation`]: crate::error::Error::LastCpuLocation
+    pub fn last_pu_location(&self, flags: CpuBindFlags) -> Result<Option<Object<'_>>, Error> {
+        Ok(self
+            .last_cpu_location(flags)?
+            .first()
+            .and_then(|os_index| self.pu_object_by_os_index(os_index as u32)))
+    }
+
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+    /////
+    /////  Memory binding
+    /////
+    /////  https://www.open-mpi.org/projects/hwloc/doc/v2.7.1/a00167.php
+    /////
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+
+    /// Set the default memory binding policy of the current process or thread to prefer the NUMA
+    /// node(s) specified by `set`.
+    ///
+    /// If neither [`MemBindFlags::PROCESS`] nor [`MemBindFlags::THREAD`] is specified, the current
+    /// process is assumed to be single-threaded. This is the most portable form as it permits
+    /// hwloc to use either process-based OS functions or thread-based OS functions, depending on
+    /// which are available.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MemBindSet`] in case of failure (e.g., if the action is not supported, or
+    /// if the binding cannot be enforced).
+    ///
+    /// [`Error::MemBindSet`]: crate::error::Error::MemBindSet
+    pub fn set_membind(
+        &self,
+        set: MemBindSet<'_>,
+        policy: MemBindPolicy,
+        flags: MemBindFlags,
+    ) -> Result<(), Error> {
+        // SAFETY: `self.topo` is a valid topology object, created via a `TopologyBuilder`, and
+        // `set` refers to a valid bitmap; hwloc does not modify either of them.
+        match unsafe {
+            hwloc2_sys::hwloc_set_membind(
+                self.topo,
+                ptr_mut_to_const(set.bitmap().as_ptr()),
+                policy as i32,
+                set.raw_flags(flags),
+            )
+        } {
+            -1 => Err(Error::MemBindSet),
+            _ => Ok(()),
+        }
+    }
+
+    /// Query the default memory binding policy and physical locality of the current process or
+    /// thread.
+    ///
+    /// Returns the nodeset and the policy of the process or thread memory binding.
+    ///
+    /// Passing [`MemBindFlags::PROCESS`] specifies that the query target is the current policies
+    /// and nodesets for all the threads in the current process. Passing [`MemBindFlags::THREAD`]
+    /// specifies that the query target is the current policy and nodeset for only the thread
+    /// invoking this function. If neither of these flags are passed (which is the most portable
+    /// method), the process is assumed to be single threaded.
+    ///
+    /// [`MemBindFlags::STRICT`] is only meaningful when [`MemBindFlags::PROCESS`] is also
+    /// specified. In this case, hwloc will check the default memory policies and nodesets for all
+    /// threads in the process, and fail if they are not identical. Otherwise, if
+    /// [`MemBindFlags::PROCESS`] is specified, the default nodeset from each thread is logically
+    /// OR'ed together; if all threads' default policies are the same, that policy is returned,
+    /// otherwise [`MemBindPolicy::Mixed`] is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MemBindGet`] in case of failure (e.g., if any other flags are specified).
+    ///
+    /// [`Error::MemBindGet`]: crate::error::Error::MemBindGet
+    pub fn membind(&self, flags: MemBindFlags) -> Result<(NodeSet, MemBindPolicy), Error> {
+        let nodeset = NodeSet::try_new_empty()?;
+        let mut policy = MemBindPolicy::Default as i32;
+        // SAFETY: `self.topo` is a valid topology object, created via a `TopologyBuilder`,
+        // `nodeset` is a valid, exclusively owned bitmap, and `policy` is valid for writes.
+        match unsafe {
+            hwloc2_sys::hwloc_get_membind(
+                self.topo,
+                nodeset.as_ptr(),
+                &mut policy,
+                flags.bits_by_nodeset(),
+            )
+        } {
+            -1 => Err(Error::MemBindGet),
+            _ => Ok((
+                nodeset,
+                MemBindPolicy::from_i32(policy).ok_or(Error::MemBindGet)?,
+            )),
+        }
+    }
+
+    /// Set the default memory binding policy of the current process or thread to prefer the NUMA
+    /// node(s) specified by `set`, until the returned guard is dropped.
+    ///
+    /// The current binding (according to [`MemBindFlags::PROCESS`] and [`MemBindFlags::THREAD`]
+    /// in `flags`) is retrieved first, and it is restored, with the same `flags` except for
+    /// [`MemBindFlags::MIGRATE`], when the guard is dropped, or explicitly via
+    /// [`MemBindGuard::restore`]. Memory that has been allocated in the meantime remains bound
+    /// as it was.
+    ///
+    /// The current binding is applied again before binding to `set`, to make sure that it can be
+    /// restored; otherwise (e.g., if its policy is [`MemBindPolicy::Mixed`]), the binding is left
+    /// untouched and an error is returned.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::MemBindGet`] if hwloc fails to retrieve the current binding.
+    /// - [`Error::MemBindSet`] if the current binding cannot be restored, or if hwloc fails to
+    ///   bind to `set`.
+    ///
+    /// [`Error::MemBindGet`]: crate::error::Error::MemBindGet
+    /// [`Error::MemBindSet`]: crate::error::Error::MemBindSet
+    pub fn membind_scoped(
+        &self,
+        set: MemBindSet<'_>,
+        policy: MemBindPolicy,
+        flags: MemBindFlags,
+    ) -> Result<MemBindGuard<'_>, Error> {
+        MemBindGuard::bind(self, set, policy, flags)
+    }
+
+    /// Set the default memory binding policy of process `pid` to prefer the NUMA node(s)
+    /// specified by `set`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ProcMemBindSet`] in case of failure (e.g., if the action is not
+    /// supported, or if the binding cannot be enforced).
+    ///
+    /// [`Error::ProcMemBindSet`]: crate::error::Error::ProcMemBindSet
+    pub fn set_proc_membind(
+        &self,
+        pid: libc::pid_t,
+        set: MemBindSet<'_>,
+        policy: MemBindPolicy,
+        flags: MemBindFlags,
+    ) -> Result<(), Error> {
+        // SAFETY: `self.topo` is a valid topology object, created via a `TopologyBuilder`, and
+        // `set` refers to a valid bitmap; hwloc does not modify either of them.
+        match unsafe {
+            hwloc2_sys::hwloc_set_proc_membind(
+                self.topo,
+                pid,
+                ptr_mut_to_const(set.bitmap().as_ptr()),
+                policy as i32,
+                set.raw_flags(flags),
+            )
+        } {
+            -1 => Err(Error::ProcMemBindSet(pid)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Query the default memory binding policy and physical locality of process `pid`.
+    ///
+    /// Returns the nodeset and the policy of the process memory binding.
+    ///
+    /// Passing [`MemBindFlags::PROCESS`] specifies that the query target is the current policies
+    /// and nodesets for all the threads in the specified process. If it is not specified (which
+    /// is the most portable method), the process is assumed to be single threaded. It does not
+    /// make sense to pass [`MemBindFlags::THREAD`] to this function.
+    ///
+    /// If [`MemBindFlags::STRICT`] is specified, hwloc will check the default memory policies and
+    /// nodesets for all threads in the specified process, and fail if they are not identical.
+    /// Otherwise, the returned nodeset is the logical OR of all threads' default nodeset; if all
+    /// threads' default policies are the same, that policy is returned, otherwise
+    /// [`MemBindPolicy::Mixed`] is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ProcMemBindGet`] in case of failure.
+    ///
+    /// [`Error::ProcMemBindGet`]: crate::error::Error::ProcMemBindGet
+    pub fn proc_membind(
+        &self,
+        pid: libc::pid_t,
+        flags: MemBindFlags,
+    ) -> Result<(NodeSet, MemBindPolicy), Error> {
+        let nodeset = NodeSet::try_new_empty()?;
+        let mut policy = MemBindPolicy::Default as i32;
+        // SAFETY: `self.topo` is a valid topology object, created via a `TopologyBuilder`,
+        // `nodeset` is a valid, exclusively owned bitmap, and `policy` is valid for writes.
+        match unsafe {
+            hwloc2_sys::hwloc_get_proc_membind(
+                self.topo,
+                pid,
+                nodeset.as_ptr(),
+                &mut policy,
+                flags.bits_by_nodeset(),
+            )
+        } {
+            -1 => Err(Error::ProcMemBindGet(pid)),
+            _ => Ok((
+                nodeset,
+                MemBindPolicy::from_i32(policy).ok_or(Error::ProcMemBindGet(pid))?,
+            )),
+        }
+    }
+
+    /// Bind the memory backing `area` to the NUMA node(s) specified by `set`.
+    ///
+    /// Passing [`MemBindFlags::MIGRATE`] also moves the pages that have already been allocated
+    /// to the target nodes, failing if they cannot be migrated (when combined with
+    /// [`MemBindFlags::STRICT`]). Since the binding is applied on whole pages, the memory of any
+    /// other data that share the first or last page with `area` is bound as well.
+    ///
+    /// Binding an empty `area` is a no-op.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::AreaMemBindSet`] in case of failure (e.g., if the action is not
+    /// supported, or if the binding cannot be enforced).
+    ///
+    /// [`Error::AreaMemBindSet`]: crate::error::Error::AreaMemBindSet
+    pub fn set_area_membind<T>(
+        &self,
+        area: &mut [T],
+        set: MemBindSet<'_>,
+        policy: MemBindPolicy,
+        flags: MemBindFlags,
+    ) -> Result<(), Error> {
+        // SAFETY: `self.topo` is a valid topology object, created via a `TopologyBuilder`, `set`
+        // refers to a valid bitmap, and `area` is a valid memory area of the given size; hwloc
+        // only changes the physical placement of its pages, not their contents.
+        match unsafe {
+            hwloc2_sys::hwloc_set_area_membind(
+                self.topo,
+                area.as_ptr() as *const libc::c_void,
+                std::mem::size_of_val(area) as _,
+                ptr_mut_to_const(set.bitmap().as_ptr()),
+                policy as i32,
+                set.raw_flags(flags),
+            )
+        } {
+            -1 => Err(Error::AreaMemBindSet),
+            _ => Ok(()),
+        }
+    }
+
+    /// Query the memory binding policy and physical locality of the memory backing `area`.
+    ///
+    /// Returns the nodeset and the policy of the memory binding.
+    ///
+    /// If [`MemBindFlags::STRICT`] is specified, hwloc will check the policies and nodesets of
+    /// all memory pages in `area`, and fail if they are not identical. Otherwise, the returned
+    /// nodeset is the logical OR of the nodesets of all pages; if all pages have the same policy,
+    /// that policy is returned, otherwise [`MemBindPolicy::Mixed`] is returned.
+    ///
+    /// Note that the binding only describes where the pages are *allowed* to be allocated; see
+    /// [`Topology::area_memlocation`] for where they actually reside.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::AreaMemBindGet`] in case of failure (e.g., if `area` is empty, or if the
+    /// action is not supported).
+    ///
+    /// [`Error::AreaMemBindGet`]: crate::error::Error::AreaMemBindGet
+    pub fn area_membind<T>(
+        &self,
+        area: &[T],
+        flags: MemBindFlags,
+    ) -> Result<(NodeSet, MemBindPolicy), Error> {
+        let nodeset = NodeSet::try_new_empty()?;
+        let mut policy = MemBindPolicy::Default as i32;
+        // SAFETY: `self.topo` is a valid topology object, created via a `TopologyBuilder`,
+        // `area` is a valid memory area of the given size, `nodeset` is a valid, exclusively
+        // owned bitmap, and `policy` is valid for writes.
+        match unsafe {
+            hwloc2_sys::hwloc_get_area_membind(
+                self.topo,
+                area.as_ptr() as *const libc::c_void,
+                std::mem::size_of_val(area) as _,
+                nodeset.as_ptr(),
+                &mut policy,
+                flags.bits_by_nodeset(),
+            )
+        } {
+            -1 => Err(Error::AreaMemBindGet),
+            _ => Ok((
+                nodeset,
+                MemBindPolicy::from_i32(policy).ok_or(Error::AreaMemBindGet)?,
+            )),
+        }
+    }
+
+    /// Query the NUMA node(s) where the memory backing `area` is physically allocated.
+    ///
+    /// If pages of `area` spread to multiple nodes, the returned nodeset is their union. Pages
+    /// that have not been touched yet are usually not allocated, and thus they do not contribute
+    /// to the returned nodeset.
+    ///
+    /// Passing [`MemBindFlags::STRICT`] and/or [`MemBindFlags::MIGRATE`] is not supported;
+    /// the rest of the flags are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::AreaMemLocation`] in case of failure (e.g., if `area` is empty, or if
+    /// the action is not supported).
+    ///
+    /// [`Error::AreaMemLocation`]: crate::error::Error::AreaMemLocation
+    pub fn area_memlocation<T>(&self, area: &[T], flags: MemBindFlags) -> Result<NodeSet, Error> {
+        let nodeset = NodeSet::try_new_empty()?;
+        // SAFETY: `self.topo` is a valid topology object, created via a `TopologyBuilder`,
+        // `area` is a valid memory area of the given size, and `nodeset` is a valid, exclusively
+        // owned bitmap.
+        match unsafe {
+            hwloc2_sys::hwloc_get_area_memlocation(
+                self.topo,
+                area.as_ptr() as *const libc::c_void,
+                std::mem::size_of_val(area) as _,
+                nodeset.as_ptr(),
+                flags.bits_by_nodeset(),
+            )
+        } {
+            -1 => Err(Error::AreaMemLocation),
+            _ => Ok(nodeset),
+        }
+    }
 
     ///////////////////////////////////////////////////////////////////////////////////////////////
     /////
@@ -464,14 +1153,11 @@ impl Topology {
     ///
     /// # Notes
     ///
-    /// - The returned cpuset is not newly allocated and should thus not be changed or freed;
-    /// [`CpuSet::clone`] must be used to obtain a local copy.
+    /// - The returned cpuset is a newly allocated copy; changing it does not affect the topology.
     /// - This is equivalent to retrieving the root object complete CPU-set.
-    ///
-    /// [`CpuSet::clone`]: crate::bitmap::Bitmap::clone
     pub fn complete_cpuset(&self) -> Result<CpuSet, Error> {
         let bmptr = unsafe { hwloc2_sys::hwloc_topology_get_complete_cpuset(self.topo) };
-        unsafe { Bitmap::from_raw(bmptr as *mut _, false) }
+        unsafe { Bitmap::copy_from_raw(bmptr) }
     }
 
     /// TODO: UNTESTED
@@ -483,14 +1169,11 @@ impl Topology {
     ///
     /// # Notes
     ///
-    /// - The returned cpuset is not newly allocated and should thus not be changed or freed;
-    /// [`CpuSet::clone`] must be used to obtain a local copy.
+    /// - The returned cpuset is a newly allocated copy; changing it does not affect the topology.
     /// - This is equivalent to retrieving the root object CPU-set.
-    ///
-    /// [`CpuSet::clone`]: crate::bitmap::Bitmap::clone
     pub fn topology_cpuset(&self) -> Result<CpuSet, Error> {
         let bmptr = unsafe { hwloc2_sys::hwloc_topology_get_topology_cpuset(self.topo) };
-        unsafe { Bitmap::from_raw(bmptr as *mut _, false) }
+        unsafe { Bitmap::copy_from_raw(bmptr) }
     }
 
     /// TODO: UNTESTED
@@ -508,14 +1191,12 @@ impl Topology {
     /// result of this function and on an object cpuset checks whether there are allowed PUs inside
     /// that object. Applying `hwloc_bitmap_and()` returns the list of these allowed PUs.
     // FIXME doclink:         ^^^^^^^^^^^^^^^^^^^^
-    /// - The returned cpuset is not newly allocated and should thus not be changed or freed,
-    /// [`CpuSet::clone`] must be used to obtain a local copy.
+    /// - The returned cpuset is a newly allocated copy; changing it does not affect the topology.
     ///
     /// [`Flags::INCLUDE_DISALLOWED`]: crate::topology::flags::Flags::INCLUDE_DISALLOWED
-    /// [`CpuSet::clone`]: crate::bitmap::Bitmap::clone
     pub fn allowed_cpuset(&self) -> Result<CpuSet, Error> {
         let bmptr = unsafe { hwloc2_sys::hwloc_topology_get_allowed_cpuset(self.topo) };
-        unsafe { Bitmap::from_raw(bmptr as *mut _, false) }
+        unsafe { Bitmap::copy_from_raw(bmptr) }
     }
 
     /// TODO: UNTESTED
@@ -526,14 +1207,11 @@ impl Topology {
     ///
     /// # Notes
     ///
-    /// - The returned nodeset is not newly allocated and should thus not be changed or freed;
-    /// [`NodeSet::clone`] must be used to obtain a local copy.
+    /// - The returned nodeset is a newly allocated copy; changing it does not affect the topology.
     /// - This is equivalent to retrieving the root object complete nodeset.
-    ///
-    /// [`NodeSet::clone`]: crate::bitmap::Bitmap::clone
     pub fn complete_nodeset(&self) -> Result<NodeSet, Error> {
         let bmptr = unsafe { hwloc2_sys::hwloc_topology_get_complete_nodeset(self.topo) };
-        unsafe { Bitmap::from_raw(bmptr as *mut _, false) }
+        unsafe { Bitmap::copy_from_raw(bmptr) }
     }
 
     /// TODO: UNTESTED
@@ -545,14 +1223,11 @@ impl Topology {
     ///
     /// # Notes
     ///
-    /// - The returned nodeset is not newly allocated and should thus not be changed or freed;
-    /// [`NodeSet::clone`] must be used to obtain a local copy.
+    /// - The returned nodeset is a newly allocated copy; changing it does not affect the topology.
     /// - This is equivalent to retrieving the root object nodeset.
-    ///
-    /// [`NodeSet::clone`]: crate::bitmap::Bitmap::clone
     pub fn topology_nodeset(&self) -> Result<NodeSet, Error> {
         let bmptr = unsafe { hwloc2_sys::hwloc_topology_get_topology_nodeset(self.topo) };
-        unsafe { Bitmap::from_raw(bmptr as *mut _, false) }
+        unsafe { Bitmap::copy_from_raw(bmptr) }
     }
 
     /// TODO: UNTESTED
@@ -571,14 +1246,12 @@ impl Topology {
     /// nodes inside that object. Applying `hwloc_bitmap_and()` returns the list of these allowed
     // FIXME doclink:                      ^^^^^^^^^^^^^^^^^^^^
     /// NUMA nodes.
-    /// - The returned nodeset is not newly allocated and should thus not be changed or freed,
-    /// [`NodeSet::clone`] must be used to obtain a local copy.
+    /// - The returned nodeset is a newly allocated copy; changing it does not affect the topology.
     ///
     /// [`Flags::INCLUDE_DISALLOWED`]: crate::topology::flags::Flags::INCLUDE_DISALLOWED
-    /// [`NodeSet::clone`]: crate::bitmap::Bitmap::clone
     pub fn allowed_nodeset(&self) -> Result<NodeSet, Error> {
         let bmptr = unsafe { hwloc2_sys::hwloc_topology_get_allowed_nodeset(self.topo) };
-        unsafe { Bitmap::from_raw(bmptr as *mut _, false) }
+        unsafe { Bitmap::copy_from_raw(bmptr) }
     }
 
     ///////////////////////////////////////////////////////////////////////////////////////////////
@@ -818,11 +1491,9 @@ impl Topology {
         }
     }
 
-    // FIXME(ckatsak): BUG; see tests.
     /// Get the next OS device in the system.
     ///
     /// Returns the first OS device if `prev` is `None`.
-    #[deprecated = "BUG: may trigger a double free() issue"]
     pub fn next_osdev<'topo: 'prev, 'prev: 'next, 'next>(
         &'topo self,
         prev: Option<Object<'prev>>,
@@ -903,22 +1574,577 @@ impl Topology {
         None
     }
 
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+    /////
+    /////  Retrieve distances between objects
+    /////
+    /////  https://www.open-mpi.org/projects/hwloc/doc/v2.7.1/a00184.php
+    /////
     ///////////////////////////////////////////////////////////////////////////////////////////////
 
-    /// Verify that the topology is compatible with the current hwloc library.
-    ///
-    /// This is useful when using the same topology structure (in memory) in different libraries
-    /// that may use different hwloc installations (for instance if one library embeds a specific
-    /// version of hwloc, while another library uses a default system-wide hwloc installation).
-    ///
-    /// If all libraries/programs use the same hwloc installation, this function always returns
-    /// success.
+    /// Retrieve the distance matrices of the topology whose kind matches `kind` (see
+    /// [`DistancesKind`] for how it is used as a filter).
     ///
     /// # Errors
     ///
-    /// Returns [`Error::TopologyAbiCheck`] if hwloc reports that the check failed.
+    /// Returns [`Error::DistancesGet`] in case of failure reported by hwloc.
     ///
-    /// # Note
+    /// [`DistancesKind`]: crate::distances::DistancesKind
+    /// [`Error::DistancesGet`]: crate::error::Error::DistancesGet
+    pub fn distances(&self, kind: DistancesKind) -> Result<Vec<Distances<'_>>, Error> {
+        Distances::collect(self, |nr, distances| {
+            // SAFETY: `self.topo` is a valid topology object, created via a `TopologyBuilder`,
+            // and `Distances::collect` provides valid `nr` and `distances` arguments.
+            unsafe {
+                hwloc2_sys::hwloc_distances_get(self.topo, nr, distances, kind.bits() as _, 0)
+            }
+        })
+    }
+
+    /// Retrieve the distance matrices of the topology for objects at depth `depth`, whose kind
+    /// matches `kind`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::DistancesGet`] in case of failure reported by hwloc.
+    ///
+    /// [`Error::DistancesGet`]: crate::error::Error::DistancesGet
+    pub fn distances_by_depth(
+        &self,
+        depth: i32,
+        kind: DistancesKind,
+    ) -> Result<Vec<Distances<'_>>, Error> {
+        Distances::collect(self, |nr, distances| {
+            // SAFETY: `self.topo` is a valid topology object, created via a `TopologyBuilder`,
+            // and `Distances::collect` provides valid `nr` and `distances` arguments.
+            unsafe {
+                hwloc2_sys::hwloc_distances_get_by_depth(
+                    self.topo,
+                    depth,
+                    nr,
+                    distances,
+                    kind.bits() as _,
+                    0,
+                )
+            }
+        })
+    }
+
+    /// Retrieve the distance matrices of the topology for objects of type `obj_type`, whose kind
+    /// matches `kind`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::DistancesGet`] in case of failure reported by hwloc.
+    ///
+    /// [`Error::DistancesGet`]: crate::error::Error::DistancesGet
+    pub fn distances_by_type(
+        &self,
+        obj_type: ObjectType,
+        kind: DistancesKind,
+    ) -> Result<Vec<Distances<'_>>, Error> {
+        Distances::collect(self, |nr, distances| {
+            // SAFETY: `self.topo` is a valid topology object, created via a `TopologyBuilder`,
+            // and `Distances::collect` provides valid `nr` and `distances` arguments.
+            unsafe {
+                hwloc2_sys::hwloc_distances_get_by_type(
+                    self.topo,
+                    obj_type as _,
+                    nr,
+                    distances,
+                    kind.bits() as _,
+                    0,
+                )
+            }
+        })
+    }
+
+    /// Retrieve the distance matrices of the topology with the given `name`.
+    ///
+    /// Usually only one matrix may match a given name. The name of the most common one is
+    /// `"NUMALatency"`; others include `"XGMIBandwidth"`, `"XGMIHops"` and `"NVLinkBandwidth"`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::DistancesGet`] in case of failure reported by hwloc, or if `name`
+    /// contains a NUL byte.
+    ///
+    /// [`Error::DistancesGet`]: crate::error::Error::DistancesGet
+    pub fn distances_by_name(&self, name: &str) -> Result<Vec<Distances<'_>>, Error> {
+        let cname = CString::new(name).map_err(|_| Error::DistancesGet)?;
+        Distances::collect(self, |nr, distances| {
+            // SAFETY: `self.topo` is a valid topology object, created via a `TopologyBuilder`,
+            // `cname` is a valid C string, and `Distances::collect` provides valid `nr` and
+            // `distances` arguments.
+            unsafe {
+                hwloc2_sys::hwloc_distances_get_by_name(self.topo, cname.as_ptr(), nr, distances, 0)
+            }
+        })
+    }
+
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+    /////
+    /////  Add distances between objects
+    /////
+    /////  https://www.open-mpi.org/projects/hwloc/doc/v2.7.1/a00186.php
+    /////
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+
+    /// Create a new [`DistancesBuilder`] for adding a new distance matrix named `name` (e.g.,
+    /// `"SocketBandwidth"`) of the given `kind` to the topology.
+    ///
+    /// `kind` must contain exactly one `FROM_*` and one `MEANS_*` flag of [`DistancesKind`];
+    /// [`DistancesKind::FROM_USER`] is usually appropriate for matrices that are provided by the
+    /// application. The matrix is only added once its objects and values
+    /// have been set and [`DistancesBuilder::commit`] is called.
+    ///
+    /// [`DistancesBuilder`]: crate::distances::DistancesBuilder
+    /// [`DistancesBuilder::commit`]: crate::distances::DistancesBuilder::commit
+    /// [`DistancesKind`]: crate::distances::DistancesKind
+    /// [`DistancesKind::FROM_USER`]: crate::distances::DistancesKind::FROM_USER
+    pub fn add_distances(&mut self, name: &str, kind: DistancesKind) -> DistancesBuilder<'_> {
+        DistancesBuilder::new(self, name, kind)
+    }
+
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+    /////
+    /////  Remove distances between objects
+    /////
+    /////  https://www.open-mpi.org/projects/hwloc/doc/v2.7.1/a00187.php
+    /////
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+
+    /// Remove all distance matrices from the topology.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::DistancesRemove`] in case of failure reported by hwloc.
+    ///
+    /// [`Error::DistancesRemove`]: crate::error::Error::DistancesRemove
+    pub fn remove_distances(&mut self) -> Result<(), Error> {
+        // SAFETY: `self.topo` is a valid topology object, created via a `TopologyBuilder`, which
+        // is exclusively borrowed.
+        match unsafe { hwloc2_sys::hwloc_distances_remove(self.topo) } {
+            -1 => Err(Error::DistancesRemove),
+            _ => {
+                self.refresh();
+                Ok(())
+            }
+        }
+    }
+
+    /// Remove the distance matrices between objects at depth `depth` from the topology.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::DistancesRemove`] in case of failure reported by hwloc.
+    ///
+    /// [`Error::DistancesRemove`]: crate::error::Error::DistancesRemove
+    pub fn remove_distances_by_depth(&mut self, depth: i32) -> Result<(), Error> {
+        // SAFETY: `self.topo` is a valid topology object, created via a `TopologyBuilder`, which
+        // is exclusively borrowed.
+        match unsafe { hwloc2_sys::hwloc_distances_remove_by_depth(self.topo, depth) } {
+            -1 => Err(Error::DistancesRemove),
+            _ => {
+                self.refresh();
+                Ok(())
+            }
+        }
+    }
+
+    /// Remove the distance matrices between objects of type `obj_type` from the topology.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::DistancesRemove`] in case of failure reported by hwloc.
+    ///
+    /// [`Error::DistancesRemove`]: crate::error::Error::DistancesRemove
+    pub fn remove_distances_by_type(&mut self, obj_type: ObjectType) -> Result<(), Error> {
+        match self.type_depth(obj_type) {
+            d if d == TypeDepth::Unknown as i32 => Ok(()),
+            d if d == TypeDepth::Multiple as i32 => {
+                // Objects of this type exist at multiple depths (e.g., Groups).
+                let depths: Vec<_> = (0..self.depth())
+                    .filter(|&depth| matches!(self.depth_type(depth), Ok(t) if t == obj_type))
+                    .collect();
+                depths
+                    .into_iter()
+                    .try_for_each(|depth| self.remove_distances_by_depth(depth))
+            }
+            depth => self.remove_distances_by_depth(depth),
+        }
+    }
+
+    /// Remove the distance matrices named `name` from the topology.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::DistancesGet`] if hwloc fails to retrieve the matrices.
+    /// - [`Error::DistancesRemove`] if hwloc fails to remove them.
+    ///
+    /// [`Error::DistancesGet`]: crate::error::Error::DistancesGet
+    /// [`Error::DistancesRemove`]: crate::error::Error::DistancesRemove
+    pub fn remove_distances_by_name(&mut self, name: &str) -> Result<(), Error> {
+        let ptrs: Vec<_> = self
+            .distances_by_name(name)?
+            .into_iter()
+            .map(Distances::into_raw)
+            .collect();
+        let mut result = Ok(());
+        for ptr in ptrs {
+            // SAFETY: `self.topo` is a valid topology object, created via a `TopologyBuilder`,
+            // which is exclusively borrowed, and `ptr` was retrieved from it, and not released.
+            if -1 == unsafe { hwloc2_sys::hwloc_distances_release_remove(self.topo, ptr) } {
+                // SAFETY: On failure, `ptr` has not been released.
+                unsafe { hwloc2_sys::hwloc_distances_release(self.topo, ptr) };
+                result = Err(Error::DistancesRemove);
+            }
+        }
+        // Some matrices may have been removed even if others could not.
+        self.refresh();
+        result
+    }
+
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+    /////
+    /////  Comparing memory node attributes for finding where to allocate on
+    /////
+    /////  https://www.open-mpi.org/projects/hwloc/doc/v2.7.1/a00188.php
+    /////
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+
+    /// Retrieve the memory attribute `id`, e.g., [`MemAttrId::BANDWIDTH`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MemAttrGet`] if there is no such attribute in the topology.
+    ///
+    /// [`Error::MemAttrGet`]: crate::error::Error::MemAttrGet
+    pub fn memattr(&self, id: MemAttrId) -> Result<MemAttr<'_>, Error> {
+        MemAttr::new(self, id)
+    }
+
+    /// Retrieve the memory attribute with the given `name`, e.g., `"Bandwidth"`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MemAttrGet`] if there is no such attribute in the topology, or if `name`
+    /// contains a NUL byte.
+    ///
+    /// [`Error::MemAttrGet`]: crate::error::Error::MemAttrGet
+    pub fn memattr_by_name(&self, name: &str) -> Result<MemAttr<'_>, Error> {
+        let cname = CString::new(name).map_err(|_| Error::MemAttrGet)?;
+        let mut id = 0;
+        // SAFETY: `self.topo` is a valid, loaded topology, `cname` is a valid C string, and `id`
+        // is a valid pointer.
+        match unsafe { hwloc2_sys::hwloc_memattr_get_by_name(self.topo, cname.as_ptr(), &mut id) } {
+            -1 => Err(Error::MemAttrGet),
+            _ => MemAttr::new(self, MemAttrId(id)),
+        }
+    }
+
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+    /////
+    /////  Managing memory attributes
+    /////
+    /////  https://www.open-mpi.org/projects/hwloc/doc/v2.7.1/a00189.php
+    /////
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+
+    /// Register a new memory attribute named `name` (e.g., `"MeasuredReadBW"`), whose values may
+    /// then be set through [`Topology::set_memattr_value`].
+    ///
+    /// `flags` must contain exactly one of [`MemAttrFlags::HIGHER_FIRST`] and
+    /// [`MemAttrFlags::LOWER_FIRST`], as well as [`MemAttrFlags::NEED_INITIATOR`] if the values
+    /// depend on where memory is accessed from.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MemAttrRegister`] in case of failure reported by hwloc (e.g., if `flags`
+    /// are invalid, or if an attribute with the same name already exists), or if `name` contains
+    /// a NUL byte.
+    ///
+    /// [`Error::MemAttrRegister`]: crate::error::Error::MemAttrRegister
+    pub fn register_memattr(
+        &mut self,
+        name: &str,
+        flags: MemAttrFlags,
+    ) -> Result<MemAttrId, Error> {
+        let cname = CString::new(name).map_err(|_| Error::MemAttrRegister(name.to_owned()))?;
+        let mut id = 0;
+        // SAFETY: `self.topo` is a valid, loaded topology, which is exclusively borrowed, `cname`
+        // is a valid C string (which hwloc copies), and `id` is a valid pointer.
+        match unsafe {
+            hwloc2_sys::hwloc_memattr_register(self.topo, cname.as_ptr(), flags.bits(), &mut id)
+        } {
+            -1 => Err(Error::MemAttrRegister(name.to_owned())),
+            _ => {
+                self.refresh();
+                Ok(MemAttrId(id))
+            }
+        }
+    }
+
+    /// Set the `value` of the memory attribute `id` for the NUMA node `target`, as seen from
+    /// `initiator`.
+    ///
+    /// `initiator` is ignored for attributes that do not have the
+    /// [`MemAttrFlags::NEED_INITIATOR`] flag, and required for those that do; it should usually
+    /// be an [`Initiator::CpuSet`] for accesses performed by CPU cores.
+    ///
+    /// Values are exported to XML along with the rest of the topology.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MemAttrSet`] in case of failure reported by hwloc (e.g., if `target` is
+    /// not a NUMA node, or if `initiator` is missing), or if `target` or the object of
+    /// `initiator` are not part of the topology.
+    ///
+    /// [`Error::MemAttrSet`]: crate::error::Error::MemAttrSet
+    pub fn set_memattr_value(
+        &mut self,
+        id: MemAttrId,
+        target: ObjectId,
+        initiator: Option<&Initiator>,
+        value: u64,
+    ) -> Result<(), Error> {
+        let target = self.object_by_id(target).ok_or(Error::MemAttrSet)?.as_ptr();
+        let mut location = initiator
+            .map(|initiator| initiator.to_raw(self).ok_or(Error::MemAttrSet))
+            .transpose()?;
+        // SAFETY: `self.topo` is a valid, loaded topology, which is exclusively borrowed, `target`
+        // is one of its objects, and `location` is either NULL or a valid location in it, which
+        // hwloc copies.
+        match unsafe {
+            hwloc2_sys::hwloc_memattr_set_value(
+                self.topo,
+                id.0,
+                target as *mut _,
+                memattr::location_ptr(&mut location),
+                0,
+                value,
+            )
+        } {
+            -1 => Err(Error::MemAttrSet),
+            _ => {
+                self.refresh();
+                Ok(())
+            }
+        }
+    }
+
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+    /////
+    /////  Modifying a loaded Topology
+    /////
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+
+    /// Restrict the topology to the given CPU set or nodeset.
+    ///
+    /// The topology is modified so as to remove all objects that are not included (or partially
+    /// included) in the CPU set `set`. All objects CPU and node sets are restricted accordingly,
+    /// and logical indexes are renumbered.
+    ///
+    /// If [`RestrictFlags::BYNODESET`] is passed in `flags`, `set` is considered a nodeset instead
+    /// of a CPU set.
+    ///
+    /// This call may not be reverted by restricting back to a larger set. Once dropped during
+    /// restriction, objects may not be brought back, except by building another topology;
+    /// [`Topology::try_clone`] may be used beforehand to keep an unrestricted copy around.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TopologyRestrict`] if the input set or flags are invalid (e.g., if `set`
+    /// does not intersect the allowed CPU set, or if [`RestrictFlags::REMOVE_CPULESS`] is combined
+    /// with [`RestrictFlags::BYNODESET`]). The topology is not modified in this case.
+    ///
+    /// # Panics
+    ///
+    /// If hwloc fails to allocate its internal data, in which case the topology has been
+    /// reinitialized by hwloc and can no longer be used.
+    ///
+    /// [`Error::TopologyRestrict`]: crate::error::Error::TopologyRestrict
+    pub fn restrict(&mut self, set: &Bitmap, flags: RestrictFlags) -> Result<(), Error> {
+        // SAFETY: `self.topo` is a valid, loaded topology, which is exclusively borrowed (i.e.,
+        // there are no `Object`s pointing into it), and `set` is a valid bitmap.
+        if -1
+            == unsafe {
+                hwloc2_sys::hwloc_topology_restrict(
+                    self.topo,
+                    ptr_mut_to_const(set.as_ptr()),
+                    flags.bits(),
+                )
+            }
+        {
+            let err = std::io::Error::last_os_error();
+            if err.raw_os_error() == Some(libc::ENOMEM) {
+                panic!(
+                    "failed to restrict the Topology, which is no longer usable: {}",
+                    err
+                );
+            }
+            return Err(Error::TopologyRestrict(flags));
+        }
+        self.refresh();
+        Ok(())
+    }
+
+    /// Change the sets of allowed PUs and NUMA nodes in the topology.
+    ///
+    /// This method only works if [`Flags::INCLUDE_DISALLOWED`] was set on the topology. It does
+    /// not modify any object, it only changes the sets returned by [`Topology::allowed_cpuset`]
+    /// and [`Topology::allowed_nodeset`].
+    ///
+    /// It is notably useful when importing a topology from another process running in a
+    /// different Linux Cgroup, or to reflect a Cgroup update without rebuilding the topology.
+    ///
+    /// Removing objects from a topology should rather be performed with [`Topology::restrict`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TopologyAllow`] if hwloc fails to change the allowed sets (e.g., if
+    /// [`Flags::INCLUDE_DISALLOWED`] was not set, or if [`Allow::Custom`] is given without any
+    /// set).
+    ///
+    /// [`Flags::INCLUDE_DISALLOWED`]: crate::topology::flags::Flags::INCLUDE_DISALLOWED
+    /// [`Error::TopologyAllow`]: crate::error::Error::TopologyAllow
+    pub fn allow(&mut self, allow: Allow<'_>) -> Result<(), Error> {
+        let (cpuset, nodeset) = match allow {
+            Allow::Custom { cpuset, nodeset } => (
+                cpuset.map_or(ptr::null(), |set| ptr_mut_to_const(set.as_ptr())),
+                nodeset.map_or(ptr::null(), |set| ptr_mut_to_const(set.as_ptr())),
+            ),
+            _ => (ptr::null(), ptr::null()),
+        };
+        // SAFETY: `self.topo` is a valid, loaded topology, which is exclusively borrowed, and
+        // `cpuset` and `nodeset` are either NULL or valid bitmaps that outlive the call.
+        match unsafe { hwloc2_sys::hwloc_topology_allow(self.topo, cpuset, nodeset, allow.flags()) }
+        {
+            -1 => Err(Error::TopologyAllow),
+            _ => {
+                self.refresh();
+                Ok(())
+            }
+        }
+    }
+
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+    /////
+    /////  Exporting Topologies to XML
+    /////
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+
+    /// Export the topology into the XML file at `path`.
+    ///
+    /// This file may be loaded later through [`TopologyBuilder::from_xml_file`].
+    ///
+    /// By default, the latest export format is used, which means older hwloc releases (e.g. v1.x)
+    /// will not be able to import it. Exporting to v1.x specific XML format is possible using
+    /// [`XmlExportFlags::V1`] but it may miss some details about the topology.
+    ///
+    /// If `path` is `"-"`, the XML output is sent to the standard output.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TopologyExportXml`] if hwloc fails to export the topology (e.g., if the
+    /// file cannot be written).
+    ///
+    /// [`Error::TopologyExportXml`]: crate::error::Error::TopologyExportXml
+    pub fn export_xml<P: AsRef<Path>>(&self, path: P, flags: XmlExportFlags) -> Result<(), Error> {
+        let path = path.as_ref();
+        let xmlpath = CString::new(path.as_os_str().as_bytes())
+            .map_err(|_| Error::TopologyExportXml(path.to_path_buf()))?;
+        // SAFETY: `self.topo` is a valid topology object, created via a `TopologyBuilder`, and
+        // `xmlpath` is a valid NUL-terminated C string that outlives the call.
+        match unsafe {
+            hwloc2_sys::hwloc_topology_export_xml(self.topo, xmlpath.as_ptr(), flags.bits())
+        } {
+            -1 => Err(Error::TopologyExportXml(path.to_path_buf())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Export the topology into a newly-allocated XML `String`.
+    ///
+    /// The returned document may be loaded later through [`TopologyBuilder::from_xml_str`].
+    ///
+    /// By default, the latest export format is used, which means older hwloc releases (e.g. v1.x)
+    /// will not be able to import it. Exporting to v1.x specific XML format is possible using
+    /// [`XmlExportFlags::V1`] but it may miss some details about the topology.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TopologyExportXmlBuffer`] if hwloc fails to export the topology, or if the
+    /// exported document is not valid UTF-8.
+    ///
+    /// [`Error::TopologyExportXmlBuffer`]: crate::error::Error::TopologyExportXmlBuffer
+    pub fn to_xml_string(&self, flags: XmlExportFlags) -> Result<String, Error> {
+        let buffer = export::XmlBuffer::export(self, flags)?;
+        buffer
+            .as_c_str()
+            .to_str()
+            .map(str::to_owned)
+            .map_err(|_| Error::TopologyExportXmlBuffer)
+    }
+
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+    /////
+    /////  Exporting Topologies to Synthetic
+    /////
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+
+    /// Export the topology as a synthetic description string.
+    ///
+    /// The returned description may be loaded later through [`TopologyBuilder::from_synthetic`].
+    /// I/O and Misc children are ignored; the synthetic string only describes normal children.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TopologyExportSynthetic`] if the topology could not be exported (e.g., if
+    /// it is not symmetric), or if the exported description is not valid UTF-8.
+    ///
+    /// [`Error::TopologyExportSynthetic`]: crate::error::Error::TopologyExportSynthetic
+    pub fn to_synthetic(&self, flags: SyntheticExportFlags) -> Result<String, Error> {
+        // hwloc suggests that 1024 bytes are enough in the vast majority of cases; grow the
+        // buffer and retry if the description got truncated.
+        let mut buf = vec![0u8; 1024];
+        loop {
+            // SAFETY: `self.topo` is a valid topology object, created via a `TopologyBuilder`,
+            // and `buf` is valid for writes of `buf.len()` bytes, including the terminating NUL.
+            let ret = unsafe {
+                hwloc2_sys::hwloc_topology_export_synthetic(
+                    self.topo,
+                    buf.as_mut_ptr() as *mut _,
+                    buf.len() as _,
+                    flags.bits(),
+                )
+            };
+            let len = usize::try_from(ret).map_err(|_| Error::TopologyExportSynthetic)?;
+            if len + 1 < buf.len() {
+                buf.truncate(len);
+                return String::from_utf8(buf).map_err(|_| Error::TopologyExportSynthetic);
+            }
+            let new_len = std::cmp::max(2 * buf.len(), len + 2);
+            buf.resize(new_len, 0);
+        }
+    }
+
+    ///////////////////////////////////////////////////////////////////////////////////////////////
+
+    /// Verify that the topology is compatible with the current hwloc library.
+    ///
+    /// This is useful when using the same topology structure (in memory) in different libraries
+    /// that may use different hwloc installations (for instance if one library embeds a specific
+    /// version of hwloc, while another library uses a default system-wide hwloc installation).
+    ///
+    /// If all libraries/programs use the same hwloc installation, this function always returns
+    /// success.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TopologyAbiCheck`] if hwloc reports that the check failed.
+    ///
+    /// # Note
     ///
     /// If sharing between processes with `hwloc_shmem_topology_write()`, the relevant check is
     /// already performed inside `hwloc_shmem_topology_adopt()`. FIXME doclink
@@ -951,16 +2177,271 @@ impl Drop for Topology {
     }
 }
 
+/// Names of the discovery components that may be built into hwloc 2.7, either statically or as
+/// plugins.
+///
+/// hwloc offers no way to enumerate the available components, so this list is used by
+/// [`TopologyBuilder::only_components`] to blacklist everything that was not requested.
+const DISCOVERY_COMPONENTS: &[&str] = &[
+    // Core components
+    "noos",
+    "synthetic",
+    "xml",
+    "xml_libxml",
+    "xml_nolibxml",
+    // OS-specific components
+    "linux",
+    "aix",
+    "darwin",
+    "freebsd",
+    "hpux",
+    "netbsd",
+    "solaris",
+    "windows",
+    // Architecture-specific components
+    "x86",
+    // I/O components
+    "pci",
+    "opencl",
+    "cuda",
+    "nvml",
+    "rsmi",
+    "levelzero",
+    "gl",
+    // Debugging components
+    "fake",
+];
+
+/// A setting that has been applied to a not-yet-loaded topology via a [`TopologyBuilder`].
+///
+/// hwloc cannot duplicate a topology before it is loaded, so settings are recorded to be replayed
+/// onto a fresh topology context by [`TopologyBuilder::try_clone`].
 #[derive(Debug, Clone)]
+enum Setting {
+    Xml(PathBuf),
+    XmlBuffer(CString),
+    Synthetic(String),
+    Pid(libc::pid_t),
+    ExcludeComponent(String),
+    Flags(flags::Flags),
+    TypeFilter(ObjectType, filters::Filter),
+    AllTypesFilter(filters::Filter),
+    CacheTypesFilter(filters::Filter),
+    ICacheTypesFilter(filters::Filter),
+    IoTypesFilter(filters::Filter),
+}
+
+#[derive(Debug)]
 pub struct TopologyBuilder {
     topo: *mut hwloc2_sys::hwloc_topology,
 
+    // The settings applied so far, in order; used to implement `try_clone()`.
+    settings: Vec<Setting>,
+
     // Used in `impl Drop` to make sure the new topology's context will not be freed after it has
     // been moved to the new `Topology` object.
     built: bool,
 }
 
+// SAFETY: The topology context is exclusively owned by the `TopologyBuilder` until it is loaded,
+// and it is not tied to the thread that created it.
+unsafe impl Send for TopologyBuilder {}
+
 impl TopologyBuilder {
+    /// Create a new [`TopologyBuilder`] that gathers topology information from the XML file at
+    /// `path`, instead of discovering the local machine.
+    ///
+    /// The file may have been generated earlier with `lstopo file.xml` (or `lstopo --of xml`).
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::TopologyInit`] if hwloc fails to initialize topology's context.
+    /// - [`Error::TopologySetXml`] if hwloc fails to read the XML file. Depending on the XML
+    ///   backend of hwloc, malformed XML is either rejected here as well, or only by
+    ///   [`TopologyBuilder::build`] with [`Error::TopologyLoadXml`].
+    ///
+    /// # Note
+    ///
+    /// For convenience, the XML backend provides empty binding hooks which just return success.
+    /// To have hwloc still actually call OS-specific hooks, [`Flags::IS_THISSYSTEM`] has to be set
+    /// to assert that the loaded file is really the underlying system.
+    ///
+    /// [`Error::TopologyInit`]: crate::error::Error::TopologyInit
+    /// [`Error::TopologySetXml`]: crate::error::Error::TopologySetXml
+    /// [`Error::TopologyLoadXml`]: crate::error::Error::TopologyLoadXml
+    /// [`Flags::IS_THISSYSTEM`]: crate::topology::flags::Flags::IS_THISSYSTEM
+    pub fn from_xml_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let mut builder = Topology::builder()?;
+        builder.apply(Setting::Xml(path.as_ref().to_path_buf()))?;
+        Ok(builder)
+    }
+
+    /// Create a new [`TopologyBuilder`] that gathers topology information from the given XML
+    /// document, instead of discovering the local machine.
+    ///
+    /// The document may have been generated earlier with `lstopo --of xml` or
+    /// [`Topology::to_xml_string`].
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::TopologyInit`] if hwloc fails to initialize topology's context.
+    /// - [`Error::TopologySetXmlBuffer`] if the document contains a NUL byte. Depending on the XML
+    ///   backend of hwloc, malformed XML is either rejected here as well, or only by
+    ///   [`TopologyBuilder::build`] with [`Error::TopologyLoadXml`].
+    ///
+    /// # Note
+    ///
+    /// For convenience, the XML backend provides empty binding hooks which just return success.
+    /// To have hwloc still actually call OS-specific hooks, [`Flags::IS_THISSYSTEM`] has to be set
+    /// to assert that the loaded document really describes the underlying system.
+    ///
+    /// [`Error::TopologyInit`]: crate::error::Error::TopologyInit
+    /// [`Error::TopologySetXmlBuffer`]: crate::error::Error::TopologySetXmlBuffer
+    /// [`Error::TopologyLoadXml`]: crate::error::Error::TopologyLoadXml
+    /// [`Flags::IS_THISSYSTEM`]: crate::topology::flags::Flags::IS_THISSYSTEM
+    pub fn from_xml_str(xml: &str) -> Result<Self, Error> {
+        let buffer = CString::new(xml).map_err(|_| Error::TopologySetXmlBuffer)?;
+        let mut builder = Topology::builder()?;
+        builder.apply(Setting::XmlBuffer(buffer))?;
+        Ok(builder)
+    }
+
+    /// Create a new [`TopologyBuilder`] that builds a synthetic topology from the given
+    /// `description`, instead of discovering the local machine.
+    ///
+    /// `description` is a space-separated string of `<type:number>` describing the object type and
+    /// arity at each level, e.g. `"pack:2 numa:1 core:8 pu:2"`. All types may be omitted
+    /// (space-separated string of numbers) so that hwloc chooses all types according to usual
+    /// topologies. See also
+    /// [Synthetic topologies](https://www.open-mpi.org/projects/hwloc/doc/v2.7.1/a00363.php) and
+    /// [`TopologyBuilder::from_synthetic_description`].
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::TopologyInit`] if hwloc fails to initialize topology's context.
+    /// - [`Error::TopologySetSynthetic`] if hwloc fails to parse the description, or if it does not
+    ///   describe a valid topology configuration.
+    ///
+    /// # Note
+    ///
+    /// For convenience, the synthetic backend provides empty binding hooks which just return
+    /// success.
+    ///
+    /// [`Error::TopologyInit`]: crate::error::Error::TopologyInit
+    /// [`Error::TopologySetSynthetic`]: crate::error::Error::TopologySetSynthetic
+    pub fn from_synthetic(description: &str) -> Result<Self, Error> {
+        let mut builder = Topology::builder()?;
+        builder.apply(Setting::Synthetic(description.to_owned()))?;
+        Ok(builder)
+    }
+
+    /// Create a new [`TopologyBuilder`] that builds a synthetic topology from the given typed
+    /// [`SyntheticDescription`], instead of discovering the local machine.
+    ///
+    /// This is the same as [`TopologyBuilder::from_synthetic`] on the string representation of
+    /// `description`.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::TopologyInit`] if hwloc fails to initialize topology's context.
+    /// - [`Error::TopologySetSynthetic`] if `description` does not describe a valid topology
+    ///   configuration (e.g., if it includes I/O or Misc object types).
+    ///
+    /// [`Error::TopologyInit`]: crate::error::Error::TopologyInit
+    /// [`Error::TopologySetSynthetic`]: crate::error::Error::TopologySetSynthetic
+    pub fn from_synthetic_description(description: &SyntheticDescription) -> Result<Self, Error> {
+        Self::from_synthetic(&description.to_string())
+    }
+
+    /// Change which process the topology is viewed from.
+    ///
+    /// On some systems, processes may have different views of the machine, for instance the set
+    /// of allowed CPUs. By default, hwloc exposes the view from the current process. Calling this
+    /// method permits to make it expose the topology of the machine from the point of view of
+    /// another process, e.g., to evaluate the allowed cpuset and nodeset (and
+    /// [`Flags::RESTRICT_TO_CPUBINDING`] or [`Flags::RESTRICT_TO_MEMBINDING`]) against it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TopologySetPid`] if hwloc fails to set the given `pid` (e.g., if this
+    /// feature is not supported on the current operating system).
+    ///
+    /// [`Flags::RESTRICT_TO_CPUBINDING`]: crate::topology::flags::Flags::RESTRICT_TO_CPUBINDING
+    /// [`Flags::RESTRICT_TO_MEMBINDING`]: crate::topology::flags::Flags::RESTRICT_TO_MEMBINDING
+    /// [`Error::TopologySetPid`]: crate::error::Error::TopologySetPid
+    pub fn for_pid(mut self, pid: libc::pid_t) -> Result<Self, Error> {
+        self.apply(Setting::Pid(pid))?;
+        Ok(self)
+    }
+
+    /// Prevent the discovery component `name` from being used when loading the topology.
+    ///
+    /// `name` is the name of a discovery component, such as `"cuda"`. For components with
+    /// multiple phases, it may also be suffixed with the name of a phase, e.g. `"linux:io"`.
+    ///
+    /// This may be used to avoid expensive parts of the discovery process. For instance,
+    /// CUDA-specific discovery may be expensive and unneeded while generic I/O discovery could
+    /// still be useful.
+    ///
+    /// This is the programmatic equivalent of listing `-name` in the `HWLOC_COMPONENTS`
+    /// environment variable.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TopologySetComponents`] if hwloc fails to blacklist the component (e.g.,
+    /// if no component is called `name`).
+    ///
+    /// [`Error::TopologySetComponents`]: crate::error::Error::TopologySetComponents
+    pub fn exclude_component(mut self, name: &str) -> Result<Self, Error> {
+        self.apply(Setting::ExcludeComponent(name.to_owned()))?;
+        Ok(self)
+    }
+
+    /// Restrict the discovery to the components in `names`, by blacklisting every other known
+    /// component.
+    ///
+    /// Names are given as in [`TopologyBuilder::exclude_component`], e.g. `&["linux", "x86"]`.
+    ///
+    /// # Note
+    ///
+    /// hwloc does not offer a way to enumerate the available discovery components, hence only the
+    /// components that are shipped with hwloc 2.7 are blacklisted. Any component that is missing
+    /// from this list, such as third-party plugins or components added by later hwloc releases,
+    /// remains enabled. Known components that are not built into the underlying hwloc
+    /// installation (which hwloc rejects with `EINVAL`) are skipped.
+    ///
+    /// Since the XML and synthetic backends are discovery components too, they must be included
+    /// in `names` when combined with [`TopologyBuilder::from_xml_file`],
+    /// [`TopologyBuilder::from_xml_str`] or [`TopologyBuilder::from_synthetic`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TopologySetComponents`] if a component name contains an interior NUL byte,
+    /// or if hwloc fails to blacklist an available component.
+    ///
+    /// [`Error::TopologySetComponents`]: crate::error::Error::TopologySetComponents
+    pub fn only_components(mut self, names: &[&str]) -> Result<Self, Error> {
+        if let Some(name) = names.iter().find(|name| name.contains('\0')) {
+            return Err(Error::TopologySetComponents((*name).to_owned()));
+        }
+        for &component in DISCOVERY_COMPONENTS {
+            // Keep a component enabled if any of its phases was requested (e.g. "linux:io").
+            if !names
+                .iter()
+                .any(|name| name.split(':').next() == Some(component))
+            {
+                match self.apply(Setting::ExcludeComponent(component.to_owned())) {
+                    // The component is not built into this hwloc installation.
+                    Err(Error::TopologySetComponents(_))
+                        if std::io::Error::last_os_error().raw_os_error() == Some(libc::EINVAL) => {
+                    }
+                    result => result?,
+                }
+            }
+        }
+        Ok(self)
+    }
+
     /// Set OR'ed flags to non-yet-loaded topology.
     ///
     /// Set a OR'ed set of [`flags::Flags`] onto a topology that was not yet loaded.
@@ -977,13 +2458,9 @@ impl TopologyBuilder {
     /// Returns [`Error::TopologyFlags`] if hwloc fails to set the given flags.
     ///
     /// [`Error::TopologyFlags`]: crate::error::Error::TopologyFlags
-    pub fn flags(self, flags: flags::Flags) -> Result<Self, Error> {
-        // SAFETY: `self.topo` is a valid topology object created via a `TopologyBuilder`, and
-        // `flags` is type checked.
-        match unsafe { hwloc2_sys::hwloc_topology_set_flags(self.topo, flags.bits()) } {
-            -1 => Err(Error::TopologyFlags(flags)),
-            _ => Ok(self),
-        }
+    pub fn flags(mut self, flags: flags::Flags) -> Result<Self, Error> {
+        self.apply(Setting::Flags(flags))?;
+        Ok(self)
     }
 
     /// Set the filtering for the given object type.
@@ -993,15 +2470,13 @@ impl TopologyBuilder {
     /// Returns [`Error::TopologySetFilter`] if hwloc fails to set the given filter.
     ///
     /// [`Error::TopologySetFilter`]: crate::error::Error::TopologySetFilter
-    pub fn type_filter(self, obj_type: ObjectType, filter: filters::Filter) -> Result<Self, Error> {
-        // SAFETY: `self.topo` is a valid topology object created via a `TopologyBuilder`, and
-        // `filter` & `obj_type` are type checked.
-        match unsafe {
-            hwloc2_sys::hwloc_topology_set_type_filter(self.topo, obj_type as u32, filter as u32)
-        } {
-            -1 => Err(Error::TopologySetFilter(obj_type, filter)),
-            _ => Ok(self),
-        }
+    pub fn type_filter(
+        mut self,
+        obj_type: ObjectType,
+        filter: filters::Filter,
+    ) -> Result<Self, Error> {
+        self.apply(Setting::TypeFilter(obj_type, filter))?;
+        Ok(self)
     }
 
     /// Set the filtering for all object types.
@@ -1013,13 +2488,9 @@ impl TopologyBuilder {
     /// Returns [`Error::TopologySetAllTypesFilter`] if hwloc fails to set the given filter.
     ///
     /// [`Error::TopologySetAllTypesFilter`]: crate::error::Error::TopologySetAllTypesFilter
-    pub fn all_types_filter(self, filter: filters::Filter) -> Result<Self, Error> {
-        // SAFETY: `self.topo` is a valid topology object created via a `TopologyBuilder`, and
-        // `filter` is type checked.
-        match unsafe { hwloc2_sys::hwloc_topology_set_all_types_filter(self.topo, filter as u32) } {
-            -1 => Err(Error::TopologySetAllTypesFilter(filter)),
-            _ => Ok(self),
-        }
+    pub fn all_types_filter(mut self, filter: filters::Filter) -> Result<Self, Error> {
+        self.apply(Setting::AllTypesFilter(filter))?;
+        Ok(self)
     }
 
     /// Set the filtering for all CPU cache object types.
@@ -1031,14 +2502,9 @@ impl TopologyBuilder {
     /// Returns [`Error::TopologySetCacheTypesFilter`] if hwloc fails to set the given filter.
     ///
     /// [`Error::TopologySetCacheTypesFilter`]: crate::error::Error::TopologySetCacheTypesFilter
-    pub fn cache_types_filter(self, filter: filters::Filter) -> Result<Self, Error> {
-        // SAFETY: `self.topo` is a valid topology object created via a `TopologyBuilder`, and
-        // `filter` is type checked.
-        match unsafe { hwloc2_sys::hwloc_topology_set_cache_types_filter(self.topo, filter as u32) }
-        {
-            -1 => Err(Error::TopologySetCacheTypesFilter(filter)),
-            _ => Ok(self),
-        }
+    pub fn cache_types_filter(mut self, filter: filters::Filter) -> Result<Self, Error> {
+        self.apply(Setting::CacheTypesFilter(filter))?;
+        Ok(self)
     }
 
     /// Set the filtering for all CPU instruction cache object types.
@@ -1050,15 +2516,9 @@ impl TopologyBuilder {
     /// Returns [`Error::TopologySetICacheTypesFilter`] if hwloc fails to set the given filter.
     ///
     /// [`Error::TopologySetICacheTypesFilter`]: crate::error::Error::TopologySetICacheTypesFilter
-    pub fn icache_types_filter(self, filter: filters::Filter) -> Result<Self, Error> {
-        // SAFETY: `self.topo` is a valid topology object created via a `TopologyBuilder`, and
-        // `filter` is type checked.
-        match unsafe {
-            hwloc2_sys::hwloc_topology_set_icache_types_filter(self.topo, filter as u32)
-        } {
-            -1 => Err(Error::TopologySetICacheTypesFilter(filter)),
-            _ => Ok(self),
-        }
+    pub fn icache_types_filter(mut self, filter: filters::Filter) -> Result<Self, Error> {
+        self.apply(Setting::ICacheTypesFilter(filter))?;
+        Ok(self)
     }
 
     /// Set the filtering for all I/O object types.
@@ -1068,22 +2528,126 @@ impl TopologyBuilder {
     /// Returns [`Error::TopologySetIOTypesFilter`] if hwloc fails to set the given filter.
     ///
     /// [`Error::TopologySetIOTypesFilter`]: crate::error::Error::TopologySetIOTypesFilter
-    pub fn io_types_filter(self, filter: filters::Filter) -> Result<Self, Error> {
-        // SAFETY: `self.topo` is a valid topology object created via a `TopologyBuilder`, and
-        // `filter` is type checked.
-        match unsafe { hwloc2_sys::hwloc_topology_set_io_types_filter(self.topo, filter as u32) } {
-            -1 => Err(Error::TopologySetIOTypesFilter(filter)),
-            _ => Ok(self),
+    pub fn io_types_filter(mut self, filter: filters::Filter) -> Result<Self, Error> {
+        self.apply(Setting::IoTypesFilter(filter))?;
+        Ok(self)
+    }
+
+    /// Create a new [`TopologyBuilder`] with the same settings as this one.
+    ///
+    /// Since hwloc cannot duplicate a topology that has not been loaded yet, a new topology
+    /// context is initialized and all settings applied to `self` so far are applied to it anew,
+    /// in the same order.
+    ///
+    /// In particular, the XML file of a builder created via [`TopologyBuilder::from_xml_file`] is
+    /// read again, so the new builder describes its current contents, which may differ from those
+    /// that `self` was created from.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::TopologyInit`] if hwloc fails to initialize the new topology's context.
+    /// - Any error that applying one of the settings to the new context may return (e.g.,
+    ///   [`Error::TopologySetXml`] if the XML file has been removed in the meantime).
+    ///
+    /// [`Error::TopologyInit`]: crate::error::Error::TopologyInit
+    /// [`Error::TopologySetXml`]: crate::error::Error::TopologySetXml
+    pub fn try_clone(&self) -> Result<Self, Error> {
+        let mut builder = Topology::builder()?;
+        for setting in &self.settings {
+            builder.apply(setting.clone())?;
+        }
+        Ok(builder)
+    }
+
+    /// Apply `setting` to the not-yet-loaded topology, and record it on success.
+    fn apply(&mut self, setting: Setting) -> Result<(), Error> {
+        // SAFETY (for all the calls below): `self.topo` is a valid topology object created via a
+        // `TopologyBuilder`, which has not been loaded yet (or `self` would have been consumed);
+        // all C strings are valid NUL-terminated strings that outlive the calls (hwloc does not
+        // keep references to them); all other arguments are type checked.
+        let ret = match &setting {
+            Setting::Xml(path) => {
+                let xmlpath = CString::new(path.as_os_str().as_bytes())
+                    .map_err(|_| Error::TopologySetXml(path.clone()))?;
+                unsafe { hwloc2_sys::hwloc_topology_set_xml(self.topo, xmlpath.as_ptr()) }
+            }
+            Setting::XmlBuffer(buffer) => {
+                // hwloc expects the size of the buffer including its trailing NUL byte.
+                let size = i32::try_from(buffer.as_bytes_with_nul().len())
+                    .map_err(|_| Error::TopologySetXmlBuffer)?;
+                unsafe {
+                    hwloc2_sys::hwloc_topology_set_xmlbuffer(self.topo, buffer.as_ptr(), size)
+                }
+            }
+            Setting::Synthetic(description) => {
+                let cdesc = CString::new(description.as_str())
+                    .map_err(|_| Error::TopologySetSynthetic(description.clone()))?;
+                unsafe { hwloc2_sys::hwloc_topology_set_synthetic(self.topo, cdesc.as_ptr()) }
+            }
+            Setting::Pid(pid) => unsafe { hwloc2_sys::hwloc_topology_set_pid(self.topo, *pid) },
+            Setting::ExcludeComponent(name) => {
+                let cname = CString::new(name.as_str())
+                    .map_err(|_| Error::TopologySetComponents(name.clone()))?;
+                unsafe {
+                    hwloc2_sys::hwloc_topology_set_components(
+                        self.topo,
+                        hwloc2_sys::hwloc_topology_components_flag_e_HWLOC_TOPOLOGY_COMPONENTS_FLAG_BLACKLIST
+                            as _,
+                        cname.as_ptr(),
+                    )
+                }
+            }
+            Setting::Flags(flags) => unsafe {
+                hwloc2_sys::hwloc_topology_set_flags(self.topo, flags.bits())
+            },
+            Setting::TypeFilter(obj_type, filter) => unsafe {
+                hwloc2_sys::hwloc_topology_set_type_filter(
+                    self.topo,
+                    *obj_type as u32,
+                    *filter as u32,
+                )
+            },
+            Setting::AllTypesFilter(filter) => unsafe {
+                hwloc2_sys::hwloc_topology_set_all_types_filter(self.topo, *filter as u32)
+            },
+            Setting::CacheTypesFilter(filter) => unsafe {
+                hwloc2_sys::hwloc_topology_set_cache_types_filter(self.topo, *filter as u32)
+            },
+            Setting::ICacheTypesFilter(filter) => unsafe {
+                hwloc2_sys::hwloc_topology_set_icache_types_filter(self.topo, *filter as u32)
+            },
+            Setting::IoTypesFilter(filter) => unsafe {
+                hwloc2_sys::hwloc_topology_set_io_types_filter(self.topo, *filter as u32)
+            },
+        };
+        if -1 == ret {
+            return Err(match setting {
+                Setting::Xml(path) => Error::TopologySetXml(path),
+                Setting::XmlBuffer(_) => Error::TopologySetXmlBuffer,
+                Setting::Synthetic(description) => Error::TopologySetSynthetic(description),
+                Setting::Pid(pid) => Error::TopologySetPid(pid),
+                Setting::ExcludeComponent(name) => Error::TopologySetComponents(name),
+                Setting::Flags(flags) => Error::TopologyFlags(flags),
+                Setting::TypeFilter(obj_type, filter) => Error::TopologySetFilter(obj_type, filter),
+                Setting::AllTypesFilter(filter) => Error::TopologySetAllTypesFilter(filter),
+                Setting::CacheTypesFilter(filter) => Error::TopologySetCacheTypesFilter(filter),
+                Setting::ICacheTypesFilter(filter) => Error::TopologySetICacheTypesFilter(filter),
+                Setting::IoTypesFilter(filter) => Error::TopologySetIOTypesFilter(filter),
+            });
         }
+        self.settings.push(setting);
+        Ok(())
     }
 
     /// Consume this [`TopologyBuilder`] to create the new [`Topology`].
     ///
     /// # Errors
     ///
-    /// Returns [`Error::TopologyLoad`] if hwloc fails to complete the initialization of the new
-    /// topology.
+    /// Returns [`Error::TopologyLoadXml`] if the topology is loaded from XML and hwloc fails to
+    /// parse it, or [`Error::TopologyLoad`] if hwloc otherwise fails to complete the
+    /// initialization of the new topology.
     ///
+    /// [`Error::TopologyLoadXml`]: crate::error::Error::TopologyLoadXml
     /// [`Error::TopologyLoad`]: crate::error::Error::TopologyLoad
     pub fn build(mut self) -> Result<Topology, Error> {
         let support = Support::try_new(self.topo)?;
@@ -1092,7 +2656,17 @@ impl TopologyBuilder {
         // SAFETY: `topo` is a freshly allocated pointer, of the correct type, and a new topology
         // context must have been allocated successfully right above.
         if -1 == unsafe { hwloc2_sys::hwloc_topology_load(self.topo) } {
-            return Err(Error::TopologyLoad);
+            // The built-in XML parser of hwloc only parses the XML when the topology is loaded.
+            let source = self.settings.iter().rfind(|setting| {
+                matches!(
+                    setting,
+                    Setting::Xml(_) | Setting::XmlBuffer(_) | Setting::Synthetic(_)
+                )
+            });
+            return Err(match source {
+                Some(Setting::Xml(_) | Setting::XmlBuffer(_)) => Error::TopologyLoadXml,
+                _ => Error::TopologyLoad,
+            });
         }
 
         self.built = true;
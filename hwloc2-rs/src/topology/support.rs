@@ -78,6 +78,12 @@ impl Support {
 /// Flags describing actual discovery support for this topology.
 pub struct Discovery(*const hwloc2_sys::hwloc_topology_discovery_support);
 
+// SAFETY: `Discovery` is only reachable through the `Support` of a `Topology`, and points to the
+// discovery flags allocated by `hwloc_topology_init()`; discovery backends only set them while
+// the topology is loaded, after which they are only read, until the topology is destroyed.
+unsafe impl Send for Discovery {}
+unsafe impl Sync for Discovery {}
+
 impl Discovery {
     /// Detecting the number of PU objects is supported.
     pub fn pu(&self) -> bool {
@@ -136,6 +142,12 @@ impl fmt::Debug for Discovery {
 /// of non-contiguous objects).
 pub struct Cpubind(*const hwloc2_sys::hwloc_topology_cpubind_support);
 
+// SAFETY: `Cpubind` is only reachable through the `Support` of a `Topology`, and points to the
+// CPU binding flags allocated by `hwloc_topology_init()`; hwloc only sets them when installing
+// the binding hooks while the topology is loaded, after which they are only read.
+unsafe impl Send for Cpubind {}
+unsafe impl Sync for Cpubind {}
+
 impl Cpubind {
     /// Binding the whole current process is supported.
     pub fn set_thisproc_cpubind(&self) -> bool {
@@ -233,6 +245,12 @@ impl fmt::Debug for Cpubind {
 /// of non-contiguous objects).
 pub struct Membind(*const hwloc2_sys::hwloc_topology_membind_support);
 
+// SAFETY: `Membind` is only reachable through the `Support` of a `Topology`, and points to the
+// memory binding flags allocated by `hwloc_topology_init()`; hwloc only sets them when installing
+// the binding hooks while the topology is loaded, after which they are only read.
+unsafe impl Send for Membind {}
+unsafe impl Sync for Membind {}
+
 impl Membind {
     /// Binding the whole current process is supported.
     pub fn set_thisproc_membind(&self) -> bool {
@@ -339,6 +357,12 @@ impl fmt::Debug for Membind {
 /// Flags describing miscellaneous features.
 pub struct Misc(*const hwloc2_sys::hwloc_topology_misc_support);
 
+// SAFETY: `Misc` is only reachable through the `Support` of a `Topology`, and points to the
+// miscellaneous flags allocated by `hwloc_topology_init()`; hwloc only sets `imported_support`
+// while the topology is loaded (e.g., from XML), after which it is only read.
+unsafe impl Send for Misc {}
+unsafe impl Sync for Misc {}
+
 impl Misc {
     /// Support was imported when importing another topology, see [`Flags::IMPORT_SUPPORT`].
     ///
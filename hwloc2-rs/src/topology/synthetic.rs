@@ -0,0 +1,167 @@
+use std::{ffi::CStr, fmt};
+
+use crate::ObjectType;
+
+/// Typed builder for the description strings of synthetic topologies.
+///
+/// A synthetic description is a space-separated list of levels, from the top of the topology
+/// (right below the Machine root object) down to the PUs. Each level consists of an object type,
+/// the number of such objects below each object of the level above (i.e., the arity), and an
+/// optional set of attributes; e.g., `"Package:2 NUMANode:1(memory=17179869184) Core:8 PU:2"`.
+///
+/// The string representation of a `SyntheticDescription` is available through its
+/// [`fmt::Display`] implementation, and it may be loaded through
+/// [`TopologyBuilder::from_synthetic_description`].
+///
+/// See also
+/// [Synthetic topologies](https://www.open-mpi.org/projects/hwloc/doc/v2.7.1/a00363.php).
+///
+/// [`TopologyBuilder::from_synthetic_description`]: crate::topology::TopologyBuilder::from_synthetic_description
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyntheticDescription {
+    levels: Vec<SyntheticLevel>,
+}
+
+impl SyntheticDescription {
+    /// Create a new, empty `SyntheticDescription`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a new level below the ones that have already been added.
+    pub fn level(mut self, level: SyntheticLevel) -> Self {
+        self.levels.push(level);
+        self
+    }
+
+    /// Append a new level of `arity` objects of type `obj_type`, without any attributes, below
+    /// the ones that have already been added.
+    pub fn objects(self, obj_type: ObjectType, arity: u32) -> Self {
+        self.level(SyntheticLevel::new(obj_type, arity))
+    }
+
+    /// The levels of this description, from top to bottom.
+    pub fn levels(&self) -> &[SyntheticLevel] {
+        &self.levels
+    }
+}
+
+impl fmt::Display for SyntheticDescription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, level) in self.levels.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", level)?;
+        }
+        Ok(())
+    }
+}
+
+/// A single level of a [`SyntheticDescription`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyntheticLevel {
+    obj_type: ObjectType,
+    arity: u32,
+    memory: Option<u64>,
+    cache_size: Option<u64>,
+}
+
+impl SyntheticLevel {
+    /// Create a new level of `arity` objects of type `obj_type` below each object of the level
+    /// above.
+    pub fn new(obj_type: ObjectType, arity: u32) -> Self {
+        Self {
+            obj_type,
+            arity,
+            memory: None,
+            cache_size: None,
+        }
+    }
+
+    /// Set the local memory (in bytes) of each object of this level.
+    ///
+    /// Only meaningful for [`ObjectType::NumaNode`] levels.
+    ///
+    /// [`ObjectType::NumaNode`]: crate::types::ObjectType::NumaNode
+    pub fn memory(mut self, bytes: u64) -> Self {
+        self.memory = Some(bytes);
+        self
+    }
+
+    /// Set the size (in bytes) of each cache of this level.
+    ///
+    /// Only meaningful for cache levels (including [`ObjectType::MemCache`]).
+    ///
+    /// [`ObjectType::MemCache`]: crate::types::ObjectType::MemCache
+    pub fn cache_size(mut self, bytes: u64) -> Self {
+        self.cache_size = Some(bytes);
+        self
+    }
+
+    /// Type of the objects of this level.
+    pub fn object_type(&self) -> ObjectType {
+        self.obj_type
+    }
+
+    /// Number of objects of this level below each object of the level above.
+    pub fn arity(&self) -> u32 {
+        self.arity
+    }
+}
+
+impl fmt::Display for SyntheticLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // SAFETY: `hwloc_obj_type_string()` returns a pointer to a static C string for any valid
+        // object type, and `self.obj_type` is type checked.
+        let name = unsafe { CStr::from_ptr(hwloc2_sys::hwloc_obj_type_string(self.obj_type as _)) }
+            .to_str()
+            .map_err(|_| fmt::Error)?;
+        write!(f, "{}:{}", name, self.arity)?;
+
+        let attrs = [
+            self.memory.map(|v| format!("memory={}", v)),
+            self.cache_size.map(|v| format!("size={}", v)),
+        ];
+        let attrs: Vec<_> = attrs.into_iter().flatten().collect();
+        if !attrs.is_empty() {
+            write!(f, "({})", attrs.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{object::Attributes, TopologyBuilder};
+
+    #[test]
+    fn synthetic_description_string() {
+        let desc = SyntheticDescription::new()
+            .objects(ObjectType::Package, 2)
+            .level(SyntheticLevel::new(ObjectType::NumaNode, 4).memory(1 << 30))
+            .level(SyntheticLevel::new(ObjectType::L3Cache, 1).cache_size(16 << 20))
+            .objects(ObjectType::Core, 8)
+            .objects(ObjectType::PU, 2);
+        assert_eq!(
+            "Package:2 NUMANode:4(memory=1073741824) L3Cache:1(size=16777216) Core:8 PU:2",
+            desc.to_string()
+        );
+        assert_eq!(5, desc.levels().len());
+        assert_eq!("", SyntheticDescription::new().to_string());
+
+        let topo = TopologyBuilder::from_synthetic(&desc.to_string())
+            .expect("failed to create the TopologyBuilder from the description string")
+            .build()
+            .expect("failed to build the Topology");
+        assert_eq!(8, topo.nbobjs_by_type(ObjectType::L3Cache));
+        let l3 = topo
+            .object_by_type(ObjectType::L3Cache, 0)
+            .expect("failed to get the first L3 cache");
+        match l3.attributes() {
+            Some(Attributes::Cache(attrs)) => assert_eq!(16 << 20, attrs.size()),
+            attrs => panic!("unexpected L3 cache attributes: {:?}", attrs),
+        }
+    }
+}